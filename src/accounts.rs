@@ -0,0 +1,546 @@
+use crate::{
+  error::ReeMetaError,
+  instruction::ReeMetadataInstruction,
+  state::UpdateType,
+};
+
+use solana_program::{
+  account_info::AccountInfo,
+  entrypoint::ProgramResult,
+  msg,
+  program_error::ProgramError,
+  pubkey::Pubkey,
+  system_program,
+  sysvar::rent::ID as RENT_ID,
+};
+
+/// what an instruction requires of one of its accounts. authorities that can be a
+/// multisig config or a delegate are only declared here, their signatures are checked
+/// by assert_update_authority
+#[derive(PartialEq, Debug, Clone)]
+pub struct AccountConstraint {
+  pub name: &'static str,
+  pub signer: bool,
+  pub writable: bool,
+  pub keys: &'static [Pubkey], // any key when empty
+}
+
+impl AccountConstraint {
+  pub const fn read(name: &'static str) -> Self {
+    AccountConstraint{ name, signer: false, writable: false, keys: &[] }
+  }
+
+  pub const fn writable(name: &'static str) -> Self {
+    AccountConstraint{ name, signer: false, writable: true, keys: &[] }
+  }
+
+  pub const fn signer(name: &'static str) -> Self {
+    AccountConstraint{ name, signer: true, writable: false, keys: &[] }
+  }
+
+  pub const fn signer_writable(name: &'static str) -> Self {
+    AccountConstraint{ name, signer: true, writable: true, keys: &[] }
+  }
+
+  pub const fn address(name: &'static str, keys: &'static [Pubkey]) -> Self {
+    AccountConstraint{ name, signer: false, writable: false, keys }
+  }
+
+  pub fn check(&self, account_info: &AccountInfo) -> ProgramResult {
+    if self.signer && !account_info.is_signer {
+      msg!("{} must sign", self.name);
+      return Err(ReeMetaError::MissingSigner.into())
+    }
+
+    if self.writable && !account_info.is_writable {
+      msg!("{} must be writable", self.name);
+      return Err(ReeMetaError::AccountNotWritable.into())
+    }
+
+    if !self.keys.is_empty() && !self.keys.contains(account_info.key) {
+      msg!("{} can't be {}", self.name, account_info.key.to_string());
+      return Err(ReeMetaError::InvalidAccountKey.into())
+    }
+
+    Ok(())
+  }
+}
+
+const SYSTEM_PROGRAM: AccountConstraint = AccountConstraint::address("system_program", &[system_program::ID]);
+const RENT: AccountConstraint = AccountConstraint::address("rent", &[RENT_ID]);
+const TOKEN_PROGRAM: AccountConstraint = AccountConstraint::address("token_program", &[spl_token::ID, spl_token_2022::ID]);
+
+// the leading accounts of every instruction, in the order documented on ReeMetadataInstruction.
+// trailing and optional accounts are checked by the handlers
+
+const CREATE_METADATA: &[AccountConstraint] = &[
+  AccountConstraint::writable("metadata"),
+  AccountConstraint::writable("mint"),
+  AccountConstraint::read("royalty_owner"),
+  AccountConstraint::signer("mint_authority"),
+  AccountConstraint::read("new_mint_authority"),
+  AccountConstraint::signer_writable("payer"),
+  SYSTEM_PROGRAM,
+  RENT,
+  TOKEN_PROGRAM,
+];
+
+const CREATE_METADATA_WITH_AUTHORITY: &[AccountConstraint] = &[
+  AccountConstraint::writable("metadata"),
+  AccountConstraint::writable("mint"),
+  AccountConstraint::read("royalty_owner"),
+  AccountConstraint::signer("mint_authority"),
+  AccountConstraint::read("new_mint_authority"),
+  AccountConstraint::signer_writable("payer"),
+  AccountConstraint::read("update_authority"),
+  SYSTEM_PROGRAM,
+  RENT,
+  TOKEN_PROGRAM,
+];
+
+const MINT_NFT: &[AccountConstraint] = &[
+  AccountConstraint::writable("mint"),
+  AccountConstraint::signer("mint_authority"),
+  AccountConstraint::writable("recipient_token_account"),
+  TOKEN_PROGRAM,
+];
+
+const LOCK_NFT: &[AccountConstraint] = &[
+  AccountConstraint::writable("metadata"),
+  AccountConstraint::signer("payer"),
+  AccountConstraint::read("update_authority"),
+];
+
+const ADD_ROYALTY: &[AccountConstraint] = &[
+  AccountConstraint::writable("metadata"),
+  AccountConstraint::signer_writable("payer"),
+  AccountConstraint::read("update_authority"),
+  AccountConstraint::read("new_royalty"),
+  SYSTEM_PROGRAM,
+  RENT,
+];
+
+const CREATE_ROYALTY_VAULT: &[AccountConstraint] = &[
+  AccountConstraint::writable("metadata"),
+  AccountConstraint::signer_writable("payer"),
+  AccountConstraint::read("update_authority"),
+  AccountConstraint::writable("vault"),
+  SYSTEM_PROGRAM,
+  RENT,
+];
+
+const CLAIM_ROYALTIES: &[AccountConstraint] = &[
+  AccountConstraint::writable("vault"),
+  AccountConstraint::signer_writable("holder"),
+];
+
+const TRANSFER: &[AccountConstraint] = &[
+  AccountConstraint::read("metadata"),
+  AccountConstraint::read("update_authority"),
+  AccountConstraint::read("mint"),
+  AccountConstraint::read("freeze_authority"),
+  AccountConstraint::signer("owner"),
+  AccountConstraint::writable("source"),
+  AccountConstraint::writable("destination"),
+  TOKEN_PROGRAM,
+];
+
+// NftTransaction and Buy
+const NFT_TRANSACTION: &[AccountConstraint] = &[
+  AccountConstraint::writable("metadata"),
+  AccountConstraint::signer_writable("payer"),
+  AccountConstraint::signer_writable("target"),
+  SYSTEM_PROGRAM,
+  AccountConstraint::read("mint"),
+  AccountConstraint::read("freeze_authority"),
+  AccountConstraint::writable("seller_token_account"),
+  AccountConstraint::writable("buyer_token_account"),
+  TOKEN_PROGRAM,
+];
+
+const IMPORT_FROM_METAPLEX: &[AccountConstraint] = &[
+  AccountConstraint::writable("metadata"),
+  AccountConstraint::read("mint"),
+  AccountConstraint::read("metaplex_metadata"),
+  AccountConstraint::signer_writable("payer"),
+  AccountConstraint::signer("update_authority"),
+  SYSTEM_PROGRAM,
+  RENT,
+];
+
+// SetPrimarySplit, SetAttribute, RemoveAttribute and UpdateUri
+const UPDATE_METADATA: &[AccountConstraint] = &[
+  AccountConstraint::writable("metadata"),
+  AccountConstraint::signer_writable("payer"),
+  AccountConstraint::read("update_authority"),
+  SYSTEM_PROGRAM,
+  RENT,
+];
+
+const INIT_MULTISIG: &[AccountConstraint] = &[
+  AccountConstraint::writable("multisig"),
+  AccountConstraint::signer_writable("payer"),
+  AccountConstraint::signer("base"),
+  SYSTEM_PROGRAM,
+  RENT,
+];
+
+const APPROVE_DELEGATE: &[AccountConstraint] = &[
+  AccountConstraint::read("metadata"),
+  AccountConstraint::signer_writable("payer"),
+  AccountConstraint::read("update_authority"),
+  AccountConstraint::read("delegate"),
+  AccountConstraint::writable("delegate_record"),
+  SYSTEM_PROGRAM,
+  RENT,
+];
+
+const REVOKE_DELEGATE: &[AccountConstraint] = &[
+  AccountConstraint::read("metadata"),
+  AccountConstraint::read("authority"),
+  AccountConstraint::writable("delegate_record"),
+  AccountConstraint::writable("receiver"),
+];
+
+const INIT_CONFIG: &[AccountConstraint] = &[
+  AccountConstraint::writable("config"),
+  AccountConstraint::signer_writable("payer"),
+  AccountConstraint::signer("admin"),
+  AccountConstraint::read("fee_recipient"),
+  SYSTEM_PROGRAM,
+  RENT,
+  AccountConstraint::read("program_data"),
+];
+
+const UPDATE_CONFIG: &[AccountConstraint] = &[
+  AccountConstraint::writable("config"),
+  AccountConstraint::signer("admin"),
+  AccountConstraint::read("new_admin"),
+  AccountConstraint::read("fee_recipient"),
+];
+
+const SET_PAUSED: &[AccountConstraint] = &[
+  AccountConstraint::writable("config"),
+  AccountConstraint::signer("admin"),
+];
+
+const QUOTE_ROYALTY: &[AccountConstraint] = &[
+  AccountConstraint::read("metadata"),
+];
+
+const UPDATE_STATS: &[AccountConstraint] = &[
+  AccountConstraint::writable("metadata"),
+  AccountConstraint::signer_writable("payer"),
+  AccountConstraint::signer("game_authority"),
+  SYSTEM_PROGRAM,
+  RENT,
+];
+
+const REVOKE: &[AccountConstraint] = &[
+  AccountConstraint::read("metadata"),
+  AccountConstraint::read("update_authority"),
+  AccountConstraint::writable("mint"),
+  AccountConstraint::read("freeze_authority"),
+  AccountConstraint::writable("token_account"),
+  AccountConstraint::address("token_program", &[spl_token_2022::ID]),
+];
+
+const FRACTIONALIZE: &[AccountConstraint] = &[
+  AccountConstraint::writable("metadata"),
+  AccountConstraint::signer_writable("payer"),
+  AccountConstraint::signer("holder"),
+  AccountConstraint::writable("distributor"),
+  AccountConstraint::writable("fraction_mint"),
+  AccountConstraint::writable("holder_token_account"),
+  SYSTEM_PROGRAM,
+  RENT,
+  TOKEN_PROGRAM,
+];
+
+const DEPOSIT_FRACTIONS: &[AccountConstraint] = &[
+  AccountConstraint::writable("distributor"),
+  AccountConstraint::writable("stake"),
+  AccountConstraint::signer_writable("owner"),
+  AccountConstraint::read("fraction_mint"),
+  AccountConstraint::writable("owner_token_account"),
+  AccountConstraint::writable("escrow_token_account"),
+  TOKEN_PROGRAM,
+  SYSTEM_PROGRAM,
+  RENT,
+];
+
+const WITHDRAW_FRACTIONS: &[AccountConstraint] = &[
+  AccountConstraint::writable("distributor"),
+  AccountConstraint::writable("stake"),
+  AccountConstraint::signer("owner"),
+  AccountConstraint::read("fraction_mint"),
+  AccountConstraint::writable("owner_token_account"),
+  AccountConstraint::writable("escrow_token_account"),
+  TOKEN_PROGRAM,
+];
+
+const CLAIM_FRACTIONAL_ROYALTIES: &[AccountConstraint] = &[
+  AccountConstraint::writable("distributor"),
+  AccountConstraint::writable("stake"),
+  AccountConstraint::signer_writable("owner"),
+];
+
+impl ReeMetadataInstruction {
+  /// constraints on the leading accounts of the instruction
+  pub fn account_constraints(&self) -> &'static [AccountConstraint] {
+    match self {
+      Self::CreateMetaData(args) => match args.metadata.update_type {
+        UpdateType::None => CREATE_METADATA,
+        _ => CREATE_METADATA_WITH_AUTHORITY,
+      },
+      Self::MintNFT() => MINT_NFT,
+      Self::LockNFT() => LOCK_NFT,
+      Self::AddRoyalty(_) => ADD_ROYALTY,
+      Self::NftTransaction(_) => NFT_TRANSACTION,
+      Self::CreateRoyaltyVault() => CREATE_ROYALTY_VAULT,
+      Self::ClaimRoyalties() => CLAIM_ROYALTIES,
+      Self::SetPrimarySplit(_) => UPDATE_METADATA,
+      Self::Transfer() => TRANSFER,
+      Self::Buy(_) => NFT_TRANSACTION,
+      Self::ImportFromMetaplex() => IMPORT_FROM_METAPLEX,
+      Self::SetAttribute(_) => UPDATE_METADATA,
+      Self::RemoveAttribute(_) => UPDATE_METADATA,
+      Self::InitMultisig(_) => INIT_MULTISIG,
+      Self::ApproveDelegate(_) => APPROVE_DELEGATE,
+      Self::RevokeDelegate() => REVOKE_DELEGATE,
+      Self::UpdateUri(_) => UPDATE_METADATA,
+      Self::InitConfig(_) => INIT_CONFIG,
+      Self::UpdateConfig(_) => UPDATE_CONFIG,
+      Self::SetPaused(_) => SET_PAUSED,
+      Self::QuoteRoyalty(_) => QUOTE_ROYALTY,
+      Self::UpdateStats(_) => UPDATE_STATS,
+      Self::Revoke() => REVOKE,
+      Self::Fractionalize(_) => FRACTIONALIZE,
+      Self::DepositFractions(_) => DEPOSIT_FRACTIONS,
+      Self::WithdrawFractions(_) => WITHDRAW_FRACTIONS,
+      Self::ClaimFractionalRoyalties() => CLAIM_FRACTIONAL_ROYALTIES,
+    }
+  }
+
+  /// creation and sales stop while the program is paused
+  pub fn is_pausable(&self) -> bool {
+    match self {
+      Self::CreateMetaData(_)
+      | Self::NftTransaction(_)
+      | Self::Buy(_)
+      | Self::ImportFromMetaplex() => true,
+      _ => false,
+    }
+  }
+
+  /// every instruction goes through here before its handler runs
+  pub fn validate_accounts(&self, accounts: &[AccountInfo]) -> ProgramResult {
+    let constraints = self.account_constraints();
+    if accounts.len() < constraints.len() {
+      msg!("expected at least {} accounts, got {}", constraints.len(), accounts.len());
+      return Err(ProgramError::NotEnoughAccountKeys)
+    }
+
+    for (constraint, account_info) in constraints.iter().zip(accounts.iter()) {
+      constraint.check(account_info)?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    instruction::{CreateMetadataArgs, MetadataArgs, MetadataArgsRRA, NftTransactionArgs},
+    state::Kind,
+  };
+  use assert_matches::assert_matches;
+
+  struct TestAccount {
+    key: Pubkey,
+    signer: bool,
+    writable: bool,
+    lamports: u64,
+    data: Vec<u8>,
+    owner: Pubkey,
+  }
+
+  impl TestAccount {
+    fn new(key: Pubkey, signer: bool, writable: bool) -> Self {
+      TestAccount{ key, signer, writable, lamports: 0, data: Vec::new(), owner: system_program::ID }
+    }
+
+    fn info(&mut self) -> AccountInfo<'_> {
+      AccountInfo::new(
+        &self.key,
+        self.signer,
+        self.writable,
+        &mut self.lamports,
+        &mut self.data,
+        &self.owner,
+        false,
+        0,
+      )
+    }
+  }
+
+  // metadata, payer, update authority, vault, system program, rent
+  fn create_royalty_vault_accounts() -> Vec<TestAccount> {
+    vec![
+      TestAccount::new(Pubkey::new_unique(), false, true),
+      TestAccount::new(Pubkey::new_unique(), true, true),
+      TestAccount::new(Pubkey::new_unique(), true, false),
+      TestAccount::new(Pubkey::new_unique(), false, true),
+      TestAccount::new(system_program::ID, false, false),
+      TestAccount::new(RENT_ID, false, false),
+    ]
+  }
+
+  fn validate_instruction(instruction: &ReeMetadataInstruction, accounts: &mut [TestAccount]) -> ProgramResult {
+    let account_infos: Vec<AccountInfo> = accounts.iter_mut().map(|account| account.info()).collect();
+    instruction.validate_accounts(&account_infos)
+  }
+
+  fn validate(accounts: &mut [TestAccount]) -> ProgramResult {
+    validate_instruction(&ReeMetadataInstruction::CreateRoyaltyVault(), accounts)
+  }
+
+  // metadata, payer, target, system program, mint, freeze authority, seller and buyer token accounts, token program
+  fn nft_transaction_accounts() -> Vec<TestAccount> {
+    vec![
+      TestAccount::new(Pubkey::new_unique(), false, true),
+      TestAccount::new(Pubkey::new_unique(), true, true),
+      TestAccount::new(Pubkey::new_unique(), true, true),
+      TestAccount::new(system_program::ID, false, false),
+      TestAccount::new(Pubkey::new_unique(), false, false),
+      TestAccount::new(Pubkey::new_unique(), false, false),
+      TestAccount::new(Pubkey::new_unique(), false, true),
+      TestAccount::new(Pubkey::new_unique(), false, true),
+      TestAccount::new(spl_token::ID, false, false),
+    ]
+  }
+
+  // mint, mint authority, recipient token account, token program
+  fn mint_nft_accounts(token_program: Pubkey) -> Vec<TestAccount> {
+    vec![
+      TestAccount::new(Pubkey::new_unique(), false, true),
+      TestAccount::new(Pubkey::new_unique(), true, false),
+      TestAccount::new(Pubkey::new_unique(), false, true),
+      TestAccount::new(token_program, false, false),
+    ]
+  }
+
+  // metadata, mint, royalty owner, mint authority, new mint authority, payer, system program, rent, token program
+  fn create_metadata_accounts() -> Vec<TestAccount> {
+    vec![
+      TestAccount::new(Pubkey::new_unique(), false, true),
+      TestAccount::new(Pubkey::new_unique(), false, true),
+      TestAccount::new(Pubkey::new_unique(), false, false),
+      TestAccount::new(Pubkey::new_unique(), true, false),
+      TestAccount::new(Pubkey::new_unique(), false, false),
+      TestAccount::new(Pubkey::new_unique(), true, true),
+      TestAccount::new(system_program::ID, false, false),
+      TestAccount::new(RENT_ID, false, false),
+      TestAccount::new(spl_token_2022::ID, false, false),
+    ]
+  }
+
+  fn create_metadata() -> ReeMetadataInstruction {
+    ReeMetadataInstruction::CreateMetaData(CreateMetadataArgs{
+      metadata: MetadataArgs{
+        kind: Kind::RoyaltyArt,
+        is_modifiable: false,
+        update_type: UpdateType::None,
+        in_collection: false,
+      },
+      aar_data: MetadataArgsRRA{
+        name: "art".to_string(),
+        symbol: "ART".to_string(),
+        uri: "https://example.com/art.json".to_string(),
+        resale_fee: 10,
+        enforced: false,
+        soulbound: false,
+      },
+    })
+  }
+
+  #[test]
+  fn test_valid_accounts() {
+    let mut accounts = create_royalty_vault_accounts();
+    assert_matches!(validate(&mut accounts), Ok(()));
+  }
+
+  #[test]
+  fn test_missing_signer() {
+    let mut accounts = create_royalty_vault_accounts();
+    accounts[1].signer = false;
+    assert_eq!(validate(&mut accounts), Err(ReeMetaError::MissingSigner.into()));
+  }
+
+  #[test]
+  fn test_not_writable() {
+    let mut accounts = create_royalty_vault_accounts();
+    accounts[0].writable = false;
+    assert_eq!(validate(&mut accounts), Err(ReeMetaError::AccountNotWritable.into()));
+  }
+
+  #[test]
+  fn test_wrong_program_id() {
+    let mut accounts = create_royalty_vault_accounts();
+    accounts[4].key = Pubkey::new_unique();
+    assert_eq!(validate(&mut accounts), Err(ReeMetaError::InvalidAccountKey.into()));
+  }
+
+  #[test]
+  fn test_too_few_accounts() {
+    let mut accounts = create_royalty_vault_accounts();
+    accounts.pop();
+    assert_eq!(validate(&mut accounts), Err(ProgramError::NotEnoughAccountKeys));
+  }
+
+  #[test]
+  fn test_extra_accounts_are_left_to_the_handler() {
+    let mut accounts = create_royalty_vault_accounts();
+    accounts.push(TestAccount::new(Pubkey::new_unique(), false, false));
+    assert_matches!(validate(&mut accounts), Ok(()));
+  }
+
+  #[test]
+  fn test_nft_transaction_payer_must_sign() {
+    let sale = ReeMetadataInstruction::NftTransaction(NftTransactionArgs{
+      amount: 1_000_000,
+      marketplace_fee: None,
+      referral_fee: None,
+    });
+    let mut accounts = nft_transaction_accounts();
+    assert_matches!(validate_instruction(&sale, &mut accounts), Ok(()));
+
+    accounts[1].signer = false;
+    assert_eq!(validate_instruction(&sale, &mut accounts), Err(ReeMetaError::MissingSigner.into()));
+  }
+
+  #[test]
+  fn test_mint_nft_token_program() {
+    let mint = ReeMetadataInstruction::MintNFT();
+    assert_matches!(validate_instruction(&mint, &mut mint_nft_accounts(spl_token::ID)), Ok(()));
+    assert_matches!(validate_instruction(&mint, &mut mint_nft_accounts(spl_token_2022::ID)), Ok(()));
+    assert_eq!(
+      validate_instruction(&mint, &mut mint_nft_accounts(Pubkey::new_unique())),
+      Err(ReeMetaError::InvalidAccountKey.into())
+    );
+  }
+
+  #[test]
+  fn test_create_metadata_mint_authority_must_sign() {
+    let mut accounts = create_metadata_accounts();
+    assert_matches!(validate_instruction(&create_metadata(), &mut accounts), Ok(()));
+
+    accounts[3].signer = false;
+    assert_eq!(
+      validate_instruction(&create_metadata(), &mut accounts),
+      Err(ReeMetaError::MissingSigner.into())
+    );
+  }
+}
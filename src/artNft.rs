@@ -2,8 +2,40 @@ use crate::{
   error::ReeMetaError,
   state::{
     Metadata,
-    ArtNft, Royalty,
-  }, instruction::{AddRoyaltyArgs, NftTransactionArgs},
+    ArtNft, Royalty, Kind,
+    RoyaltyVault,
+    ProgramConfig,
+    FractionDistributor,
+    PREFIX, VAULT_PREFIX, FREEZE_PREFIX, DISTRIBUTOR_PREFIX,
+    SCOPE_URI, SCOPE_ATTRIBUTES, SCOPE_ROYALTIES,
+  }, instruction::{
+    CreateMetadataArgs,
+    AddRoyaltyArgs,
+    NftTransactionArgs,
+    SetPrimarySplitArgs,
+    SetAttributeArgs,
+    RemoveAttributeArgs,
+    UpdateUriArgs,
+    QuoteRoyaltyArgs,
+    FractionalizeArgs,
+  },
+  kind::{CreateAccounts, NftKind},
+  pda::{find_royalty_vault_address, find_freeze_authority_address, find_distributor_address},
+  quote::{quote_payout, quote_sale, validate_sale_fees},
+  utils::{
+    assert_buyer,
+    assert_nft_transfer,
+    assert_owned_by,
+    assert_token_program,
+    assert_update_authority,
+    assert_update_authority_or_delegate,
+    create_pda_account,
+    load_program_config,
+    resize_account,
+    transfer_nft,
+    unpack_mint,
+    unpack_token_account,
+  },
 };
 use borsh::BorshSerialize;
 
@@ -12,38 +44,172 @@ use solana_program::{
   entrypoint::ProgramResult,
   system_program,
   sysvar::{rent::{Rent, ID as RENT_ID}, Sysvar}, 
-  program::invoke, 
+  program::{invoke, invoke_signed, set_return_data}, 
+  program_error::ProgramError,
+  program_option::COption,
+  pubkey::Pubkey,
   system_instruction, 
   msg,
 };
 
-use percentage::Percentage;
+use spl_token_2022::{
+  extension::{StateWithExtensions, permanent_delegate::PermanentDelegate},
+  state::{Mint, Account as TokenAccount},
+};
 
-pub fn lock_nft(
-  mut metadata: Metadata<ArtNft>,
-  metadata_account_info: &AccountInfo,
-  update_authority_account_info: &AccountInfo,
+/// create the metadata of an ArtNFT, the royalty owner starts with all the shares
+pub fn create(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: CreateMetadataArgs,
 ) -> ProgramResult {
-  if !metadata.is_modifiable {
-    return Err(ReeMetaError::AlreadyLocked.into())
+  let create_accounts = CreateAccounts::from_accounts(program_id, accounts, &data.metadata)?;
+
+  let genesis_royalty = Royalty{
+    address: *create_accounts.owner.key,
+    share: 100, 
+    verified: true
+  };
+
+  let mut royalties: Vec<Royalty> = Vec::<Royalty>::new();
+  royalties.push(genesis_royalty);
+
+  let art_nft: ArtNft = ArtNft{
+    name: data.aar_data.name,
+    symbol: data.aar_data.symbol,
+    uri: data.aar_data.uri,
+    resale_fee: data.aar_data.resale_fee,
+    initial_sale: false,
+    collection: None,
+    royalties: royalties,
+    royalty_vault: false,
+    primary_split: Vec::new(),
+    enforced: data.aar_data.enforced,
+    attributes: Vec::new(),
+    soulbound: data.aar_data.soulbound,
+  };
+
+  art_nft.validate()?;
+
+  msg!("build Metadata");
+  let metadata = create_accounts.new_metadata(&data.metadata, art_nft);
+
+  // the issuer revokes a soulbound NFT by burning it as the permanent delegate
+  if metadata.data.soulbound {
+    let (freeze_key, _) = find_freeze_authority_address(program_id, create_accounts.mint.key);
+    assert_permanent_delegate(create_accounts.mint, create_accounts.token_program, &freeze_key)?;
   }
 
-  if metadata.update_authority == Option::None {
-    return Err(ReeMetaError::NoUpdateAuthority.into())
+  // soulbound NFTs are frozen by the same PDA, MintNFT freezes the recipient account
+  if metadata.data.enforced || metadata.data.soulbound {
+    hand_over_freeze_authority(program_id, &create_accounts)?;
   }
 
-  if !update_authority_account_info.is_signer || metadata.update_authority != Some(*update_authority_account_info.key) {
-    return Err(ReeMetaError::InvalidUpdateAuthority.into())
+  create_accounts.create_metadata(program_id, &metadata)
+}
+
+/// the mint must be a Token-2022 mint with delegate as its permanent delegate
+fn assert_permanent_delegate(
+  mint_account_info: &AccountInfo,
+  token_program_info: &AccountInfo,
+  delegate: &Pubkey,
+) -> ProgramResult {
+  // only Token-2022 has a delegate that can burn from any account
+  if *token_program_info.key != spl_token_2022::ID {
+    return Err(ReeMetaError::NoPermanentDelegate.into())
   }
 
-  // validated data lock the NFT
-  metadata.is_modifiable = false;
+  assert_owned_by(mint_account_info, token_program_info.key)?;
+  let permanent_delegate: Option<Pubkey> = {
+    let data = mint_account_info.data.borrow();
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    match mint.get_extension::<PermanentDelegate>() {
+      Ok(extension) => Option::<Pubkey>::from(extension.delegate),
+      Err(_) => None,
+    }
+  };
+  if permanent_delegate != Some(*delegate) {
+    msg!("the freeze authority PDA must be the permanent delegate of the mint");
+    return Err(ReeMetaError::NoPermanentDelegate.into())
+  }
+
+  Ok(())
+}
+
+/// give the freeze authority of a mint without supply to the freeze authority PDA
+fn hand_over_freeze_authority(program_id: &Pubkey, create_accounts: &CreateAccounts) -> ProgramResult {
+  let mint_account_info = create_accounts.mint;
+  let mint_authority_account_info = create_accounts.mint_authority;
+  let token_info = create_accounts.token_program;
+
+  let mint: Mint = unpack_mint(mint_account_info, token_info.key)?;
+  if mint.supply != 0 {
+    msg!("royalties must be enforced before any token is minted");
+    return Err(ReeMetaError::InvalidInstruction.into())
+  }
+
+  let (freeze_key, _) = find_freeze_authority_address(program_id, mint_account_info.key);
+  match mint.freeze_authority {
+    COption::Some(key) if key == freeze_key => {},
+    COption::Some(key) if key == *mint_authority_account_info.key => {
+      msg!("hand the freeze authority to the freeze authority PDA");
+      invoke(
+        &spl_token_2022::instruction::set_authority(
+          token_info.key,
+          mint_account_info.key,
+          Some(&freeze_key),
+          spl_token_2022::instruction::AuthorityType::FreezeAccount,
+          mint_authority_account_info.key,
+          &[mint_authority_account_info.key]
+        )?,
+        &[
+          mint_account_info.clone(),
+          mint_authority_account_info.clone(),
+        ]
+      )?;
+    },
+    _ => {
+      msg!("mint authority must be the freeze authority to enforce royalties");
+      return Err(ReeMetaError::InvalidFreezeAuthority.into())
+    }
+  }
+
+  Ok(())
+}
+
+/// the account of a royalty is found by key and must not be the distributor of a fractionalized share
+fn assert_not_distributor(
+  program_id: &Pubkey,
+  mint: &Pubkey,
+  address: &Pubkey,
+  accounts: &[AccountInfo],
+) -> ProgramResult {
+  let account_info = match accounts.iter().find(|account| account.key == address) {
+    None => {
+      msg!("royalty account {} missing", address.to_string());
+      return Err(ReeMetaError::RoyaltyAddressInvalid.into())
+    },
+    Some(account_info) => account_info,
+  };
+
+  if account_info.owner != program_id {
+    return Ok(())
+  }
+
+  let is_distributor = FractionDistributor::from_account_info(account_info)
+    .map(|distributor| distributor.is_initialized
+      && find_distributor_address(program_id, mint, &distributor.holder).0 == *address)
+    .unwrap_or(false);
+  if is_distributor {
+    msg!("royalty {} is fractionalized", address.to_string());
+    return Err(ReeMetaError::InvalidDistributor.into())
+  }
 
-  metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
   Ok(())
 }
 
 pub fn add_royalty(
+  program_id: &Pubkey,
   accounts: &[AccountInfo],
   mut metadata: Metadata<ArtNft>,
   data: AddRoyaltyArgs,
@@ -64,13 +230,15 @@ pub fn add_royalty(
     return Err(ReeMetaError::AlreadyLocked.into())
   }
 
-  if metadata.update_authority == Option::None {
-    return Err(ReeMetaError::NoUpdateAuthority.into())
-  }
-
-  if !update_authority_account_info.is_signer || metadata.update_authority != Some(*update_authority_account_info.key) {
-    return Err(ReeMetaError::InvalidUpdateAuthority.into())
-  }
+  assert_update_authority_or_delegate(
+    program_id,
+    metadata_account_info.key,
+    &metadata.update_type,
+    &metadata.update_authority,
+    update_authority_account_info,
+    accounts,
+    SCOPE_ROYALTIES,
+  )?;
 
   let mut art_nft = metadata.data.clone();
 
@@ -78,6 +246,9 @@ pub fn add_royalty(
     return Err(ReeMetaError::InsufficientShare.into())
   }
 
+  // a fractionalized share belongs to the fraction holders
+  assert_not_distributor(program_id, &metadata.mint, &art_nft.royalties[0].address, accounts)?;
+
   // validations complete
   let new_royalty = Royalty{
     address: *new_royalty_account_info.key,
@@ -88,135 +259,1326 @@ pub fn add_royalty(
   art_nft.royalties[0].share = art_nft.royalties[0].share - data.share;
   art_nft.royalties.push(new_royalty);
 
+  art_nft.validate()?;
+
   metadata.data = art_nft;
 
   // calculate new rent
   let rent = &Rent::from_account_info(rent_info)?;
 
+  resize_account(
+    metadata_account_info,
+    payer_account_info,
+    system_info,
+    rent,
+    metadata.clone().size()
+  )?;
+
+  metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
+
+  Ok(())
+}
+
+pub fn set_primary_split(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  mut metadata: Metadata<ArtNft>,
+  data: SetPrimarySplitArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+  let payer_account_info = next_account_info(account_iter)?;
+  let update_authority_account_info = next_account_info(account_iter)?;
+  let system_info = next_account_info(account_iter)?;
+  let rent_info = next_account_info(account_iter)?;
+
+  if *system_info.key != system_program::ID || *rent_info.key != RENT_ID {
+    return Err(ReeMetaError::InvalidInstruction.into())
+  }
+
+  if !metadata.is_modifiable {
+    return Err(ReeMetaError::AlreadyLocked.into())
+  }
+
+  assert_update_authority_or_delegate(
+    program_id,
+    metadata_account_info.key,
+    &metadata.update_type,
+    &metadata.update_authority,
+    update_authority_account_info,
+    accounts,
+    SCOPE_ROYALTIES,
+  )?;
+
+  if metadata.data.initial_sale {
+    return Err(ReeMetaError::InitialSaleCompleted.into())
+  }
+
+  let mut split: Vec<Royalty> = Vec::with_capacity(data.shares.len());
+  for share in data.shares.iter() {
+    let split_account_info = next_account_info(account_iter)?;
+    split.push(Royalty{
+      address: *split_account_info.key,
+      share: *share,
+      verified: true,
+    });
+  }
+
+  let mut art_nft = metadata.data.clone();
+  art_nft.primary_split = split;
+  art_nft.validate()?;
+
+  metadata.data = art_nft;
+
+  let rent = &Rent::from_account_info(rent_info)?;
+
+  resize_account(
+    metadata_account_info,
+    payer_account_info,
+    system_info,
+    rent,
+    metadata.clone().size()
+  )?;
+
+  metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
+
+  Ok(())
+}
+
+/// shared by the attribute instructions, validates and writes the new attributes
+fn update_attributes(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  mut metadata: Metadata<ArtNft>,
+  attributes: Vec<(String, String)>,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+  let payer_account_info = next_account_info(account_iter)?;
+  let update_authority_account_info = next_account_info(account_iter)?;
+  let system_info = next_account_info(account_iter)?;
+  let rent_info = next_account_info(account_iter)?;
+
+  if *system_info.key != system_program::ID || *rent_info.key != RENT_ID {
+    return Err(ReeMetaError::InvalidInstruction.into())
+  }
+
+  if !metadata.is_modifiable {
+    return Err(ReeMetaError::AlreadyLocked.into())
+  }
+
+  assert_update_authority_or_delegate(
+    program_id,
+    metadata_account_info.key,
+    &metadata.update_type,
+    &metadata.update_authority,
+    update_authority_account_info,
+    accounts,
+    SCOPE_ATTRIBUTES,
+  )?;
+
+  let mut art_nft = metadata.data.clone();
+  art_nft.attributes = attributes;
+  art_nft.validate()?;
+
+  metadata.data = art_nft;
+
+  let rent = &Rent::from_account_info(rent_info)?;
+
+  resize_account(
+    metadata_account_info,
+    payer_account_info,
+    system_info,
+    rent,
+    metadata.clone().size()
+  )?;
+
+  metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
+
+  Ok(())
+}
+
+pub fn set_attribute(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  metadata: Metadata<ArtNft>,
+  data: SetAttributeArgs,
+) -> ProgramResult {
+  let mut attributes = metadata.data.attributes.clone();
+  match attributes.iter_mut().find(|(key, _)| *key == data.key) {
+    Some((_, value)) => *value = data.value,
+    None => attributes.push((data.key, data.value)),
+  }
+
+  update_attributes(program_id, accounts, metadata, attributes)
+}
+
+pub fn remove_attribute(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  metadata: Metadata<ArtNft>,
+  data: RemoveAttributeArgs,
+) -> ProgramResult {
+  let mut attributes = metadata.data.attributes.clone();
+  let index = match attributes.iter().position(|(key, _)| *key == data.key) {
+    None => return Err(ReeMetaError::AttributeNotFound.into()),
+    Some(index) => index,
+  };
+  attributes.remove(index);
+
+  update_attributes(program_id, accounts, metadata, attributes)
+}
+
+pub fn update_uri(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  mut metadata: Metadata<ArtNft>,
+  data: UpdateUriArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+  let payer_account_info = next_account_info(account_iter)?;
+  let update_authority_account_info = next_account_info(account_iter)?;
+  let system_info = next_account_info(account_iter)?;
+  let rent_info = next_account_info(account_iter)?;
+
+  if *system_info.key != system_program::ID || *rent_info.key != RENT_ID {
+    return Err(ReeMetaError::InvalidInstruction.into())
+  }
+
+  if !metadata.is_modifiable {
+    return Err(ReeMetaError::AlreadyLocked.into())
+  }
+
+  assert_update_authority_or_delegate(
+    program_id,
+    metadata_account_info.key,
+    &metadata.update_type,
+    &metadata.update_authority,
+    update_authority_account_info,
+    accounts,
+    SCOPE_URI,
+  )?;
+
+  metadata.data.uri = data.uri;
+
+  let rent = &Rent::from_account_info(rent_info)?;
+
+  resize_account(
+    metadata_account_info,
+    payer_account_info,
+    system_info,
+    rent,
+    metadata.clone().size()
+  )?;
+
+  metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
+
+  Ok(())
+}
+
+/// quote the royalties on a sale of amount and return them to the caller. the seller
+/// remainder is net of the protocol fee when the config is passed
+pub fn quote_royalty(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  metadata: Metadata<ArtNft>,
+  data: QuoteRoyaltyArgs,
+) -> ProgramResult {
+  let config = match accounts.get(1) {
+    None => ProgramConfig::default(),
+    Some(_) => load_program_config(program_id, accounts)?,
+  };
+
+  let quote = quote_payout(&metadata, data.amount, &config)?.royalty_quote();
+  msg!("{} royalties, seller remainder {}", quote.royalties.len(), quote.seller);
+
+  set_return_data(&quote.try_to_vec()?);
+  Ok(())
+}
+
+pub fn create_royalty_vault(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  mut metadata: Metadata<ArtNft>,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+  let payer_account_info = next_account_info(account_iter)?;
+  let update_authority_account_info = next_account_info(account_iter)?;
+  let vault_account_info = next_account_info(account_iter)?;
+  let system_info = next_account_info(account_iter)?;
+  let rent_info = next_account_info(account_iter)?;
+
+  if *system_info.key != system_program::ID || *rent_info.key != RENT_ID {
+    return Err(ReeMetaError::InvalidInstruction.into())
+  }
+
+  if !metadata.is_modifiable {
+    return Err(ReeMetaError::AlreadyLocked.into())
+  }
+
+  assert_update_authority_or_delegate(
+    program_id,
+    metadata_account_info.key,
+    &metadata.update_type,
+    &metadata.update_authority,
+    update_authority_account_info,
+    accounts,
+    SCOPE_ROYALTIES,
+  )?;
+
+  if metadata.data.royalty_vault {
+    return Err(ReeMetaError::VaultAlreadyEnabled.into())
+  }
+
+  let rent = &Rent::from_account_info(rent_info)?;
+
+  let mut vault = load_or_create_vault(
+    program_id,
+    &metadata.mint,
+    vault_account_info,
+    payer_account_info,
+    system_info,
+    rent,
+  )?;
+
+  // start with a balance for every holder so buyers don't pay for the space
+  for royalty in metadata.data.royalties.iter().chain(metadata.data.primary_split.iter()) {
+    vault.credit(&royalty.address, 0);
+  }
+
+  resize_vault(
+    &vault,
+    vault_account_info,
+    payer_account_info,
+    system_info,
+    rent,
+  )?;
+
+  vault.serialize(&mut *vault_account_info.data.borrow_mut())?;
+
+  metadata.data.royalty_vault = true;
+
+  // metadata from before the vault existed has no room for the appended fields yet
+  resize_account(
+    metadata_account_info,
+    payer_account_info,
+    system_info,
+    rent,
+    metadata.size(),
+  )?;
+
+  metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
+
+  Ok(())
+}
+
+/// load the royalty vault, creating the PDA first if it doesn't exist yet
+fn load_or_create_vault<'a>(
+  program_id: &Pubkey,
+  mint: &Pubkey,
+  vault_account_info: &AccountInfo<'a>,
+  payer_account_info: &AccountInfo<'a>,
+  system_info: &AccountInfo<'a>,
+  rent: &Rent,
+) -> Result<RoyaltyVault, ProgramError> {
+  let (vault_key, vault_bump_seed) = find_royalty_vault_address(program_id, mint);
+  if vault_key != *vault_account_info.key {
+    msg!("Invalid vault PDA");
+    return Err(ReeMetaError::InvalidVault.into())
+  }
+
+  if !vault_account_info.data_is_empty() {
+    assert_owned_by(vault_account_info, program_id)?;
+    let vault = RoyaltyVault::from_account_info(vault_account_info)?;
+    if !vault.is_initialized {
+      return Err(ReeMetaError::Uninitialized.into())
+    }
+    return Ok(vault)
+  }
+
+  let vault_authority_seeds = &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    mint.as_ref(),
+    VAULT_PREFIX.as_bytes(),
+    &[vault_bump_seed]
+  ];
+
+  let vault = RoyaltyVault{
+    is_initialized: true,
+    mint: *mint,
+    balances: Vec::new(),
+  };
+
+  create_pda_account(
+    payer_account_info,
+    vault_account_info,
+    system_info,
+    program_id,
+    rent,
+    vault.size(),
+    vault_authority_seeds,
+  )?;
+
+  Ok(vault)
+}
+
+/// move the credited lamports into the vault and add them to each holder's balance
+fn credit_vault<'a>(
+  program_id: &Pubkey,
+  mint: &Pubkey,
+  vault_account_info: &AccountInfo<'a>,
+  payer_account_info: &AccountInfo<'a>,
+  system_info: &AccountInfo<'a>,
+  credits: &Vec<(Pubkey, u64)>,
+) -> ProgramResult {
+  let rent = &Rent::get()?;
+  let mut vault = load_or_create_vault(
+    program_id,
+    mint,
+    vault_account_info,
+    payer_account_info,
+    system_info,
+    rent,
+  )?;
+
+  let mut total: u64 = 0;
+  for (address, amount) in credits.iter() {
+    msg!("{} accrues {} lamports in the vault", address.to_string(), amount);
+    vault.credit(address, *amount);
+    total = total.checked_add(*amount).ok_or(ReeMetaError::MathOverflow)?;
+  }
+
+  if total > 0 {
+    invoke(
+      &system_instruction::transfer(
+        payer_account_info.key,
+        vault_account_info.key,
+        total
+      ),
+      &[
+        payer_account_info.clone(),
+        vault_account_info.clone(),
+        system_info.clone()
+      ]
+    )?;
+  }
+
+  // holders added after the vault was created need room for a balance
+  resize_vault(
+    &vault,
+    vault_account_info,
+    payer_account_info,
+    system_info,
+    rent,
+  )?;
+
+  vault.serialize(&mut *vault_account_info.data.borrow_mut())?;
+  Ok(())
+}
+
+/// resize the vault for its balances. the vault holds the balances on top of its rent
+/// exemption, so the top up is counted against the lamports left once they are set aside
+fn resize_vault<'a>(
+  vault: &RoyaltyVault,
+  vault_account_info: &AccountInfo<'a>,
+  payer_account_info: &AccountInfo<'a>,
+  system_info: &AccountInfo<'a>,
+  rent: &Rent,
+) -> ProgramResult {
+  let size = vault.size();
   let required_lamports = rent
-    .minimum_balance(metadata.clone().size())
-    .max(1)
-    .saturating_sub(metadata_account_info.lamports());
+    .minimum_balance(size)
+    .checked_add(vault.total_balance()?)
+    .ok_or(ReeMetaError::MathOverflow)?
+    .saturating_sub(vault_account_info.lamports());
 
   if required_lamports > 0 {
     invoke(
       &system_instruction::transfer(
-        payer_account_info.key, 
-        metadata_account_info.key,
+        payer_account_info.key,
+        vault_account_info.key,
         required_lamports
       ),
       &[
         payer_account_info.clone(),
-        metadata_account_info.clone(),
-        system_info.clone(),
+        vault_account_info.clone(),
+        system_info.clone()
       ]
     )?;
   }
 
-  metadata_account_info.realloc(metadata.clone().size(), false)?;
-
-  metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
+  if vault_account_info.data_len() != size {
+    vault_account_info.realloc(size, false)?;
+  }
 
   Ok(())
 }
 
-pub fn nft_transaction (
-  // program_id: &Pubkey,
-  accounts: &[AccountInfo],
-  mut metadata: Metadata<ArtNft>,
-  data: NftTransactionArgs,
+/// a transfer is rent safe when the recipient is left rent exempt
+fn is_rent_safe(rent: &Rent, account_info: &AccountInfo, amount: u64) -> bool {
+  rent.is_exempt(account_info.lamports().saturating_add(amount), account_info.data_len())
+}
+
+/// pay the sale amount out to the sale fees, the royalty system and the target as quoted by
+/// quote_sale. the fee recipients, the royalty accounts and the vault are read from
+/// account_iter in that order
+fn pay_out<'a, 'b: 'a, I: Iterator<Item = &'a AccountInfo<'b>>>(
+  program_id: &Pubkey,
+  metadata: &Metadata<ArtNft>,
+  payer_account_info: &AccountInfo<'b>,
+  target_account_info: &AccountInfo<'b>,
+  system_info: &AccountInfo<'b>,
+  account_iter: &mut I,
+  protocol_fee: Option<(&'a AccountInfo<'b>, u16)>,
+  data: &NftTransactionArgs,
 ) -> ProgramResult {
-  let account_iter = &mut accounts.iter();
-  let metadata_account_info = next_account_info(account_iter)?;
-  let payer_account_info = next_account_info(account_iter)?;
-  let target_account_info = next_account_info(account_iter)?;
-  let system_info = next_account_info(account_iter)?;
+  let rent = Rent::get()?;
 
-  if *system_info.key != system_program::ID {
-    msg!("Invalid system account");
-    return Err(ReeMetaError::InvalidInstruction.into())
+  // payouts that can't be transferred now are credited to the recipient in the vault
+  let mut carried: Vec<(Pubkey, u64)> = Vec::new();
+
+  // protocol, marketplace and referral fees come out of the seller's portion, never the resale
+  // royalties. the initial sale only takes the protocol fee, before the split
+  let mut fees: Vec<(&AccountInfo<'b>, u16)> = Vec::new();
+  if let Some(protocol_fee) = protocol_fee {
+    fees.push(protocol_fee);
   }
 
-  // check the initial sale 
-  // non mut vars can be set once then unchanged.
-  let royalty_payout: u64;
-  let mut target_payout: u64 = 0;
+  validate_sale_fees(metadata, data)?;
+  for fee in [&data.marketplace_fee, &data.referral_fee] {
+    let fee = match fee {
+      None => continue,
+      Some(fee) => fee,
+    };
 
-  // TODO: add to the instruction a royalty flag so non initial sales can go full royalty;
-  if !metadata.data.initial_sale {
+    let fee_account_info = next_account_info(account_iter)?;
+    if fee.recipient != *fee_account_info.key {
+      return Err(ReeMetaError::InvalidSaleFee.into())
+    }
+    fees.push((fee_account_info, fee.basis_points));
+  }
+
+  let fee_keys: Vec<(Pubkey, u16)> = fees.iter()
+    .map(|(fee_account_info, basis_points)| (*fee_account_info.key, *basis_points))
+    .collect();
+  let plan = quote_sale(metadata, data.amount, &fee_keys).map_err(|err| {
+    msg!("sale fees are larger than the seller's portion");
+    err
+  })?;
+
+  if plan.initial_sale {
     msg!("initial sale detected");
-    // initial sale has not been done yet all goes to royalties
-    royalty_payout = data.amount;
   } else {
     msg!("not an initial sale");
-    // initial sale occured this is a secondary market transaction
-    let percentage_rate = Percentage::from(metadata.data.resale_fee);
-    royalty_payout = percentage_rate.apply_to(data.amount);
-    target_payout = data.amount - royalty_payout;
-  }
-  msg!("amount was: {}", data.amount);
-  msg!("royalty_payout is: {}", royalty_payout);
-
-  // for v1 the remaining accounts much be in the same order for the accounts
-  let mut current_payout = 0;
-  for (i, royalty) in metadata.data.royalties.iter().enumerate() {
-    let royalty_account_info = next_account_info(account_iter)?;
-    if royalty.address != *royalty_account_info.key {
-      return Err(ReeMetaError::RoyaltyAddressInvalid.into())
-    }
-    let mut amount: u64 = 0;
-    if i == metadata.data.royalties.len() - 1 {
-      // make sure the last royalty gets the remaining payout to ensure full transfer of funds
-      amount = royalty_payout - current_payout;
-    } else {
-      let percentage = Percentage::from(royalty.share);
-      amount = percentage.apply_to(royalty_payout);
-      current_payout += amount;
+  }
+  msg!("amount was: {}", plan.amount);
+  msg!("royalty_payout is: {}", plan.royalty_total());
+
+  for ((fee_account_info, _), fee) in fees.iter().zip(plan.fees.iter()) {
+    if fee.amount == 0 {
+      continue;
     }
 
-    msg!("royalty {} getting {} percentage totaling {} lamports", royalty_account_info.key.to_string(), royalty.share, amount);
+    if !is_rent_safe(&rent, fee_account_info, fee.amount) {
+      msg!("sale fee to {} carried to the vault", fee.address.to_string());
+      carried.push((fee.address, fee.amount));
+      continue;
+    }
 
-    // pay amount to this user
+    msg!("sale fee to {} of {} lamports", fee.address.to_string(), fee.amount);
     invoke(
       &system_instruction::transfer(
-        payer_account_info.key, 
-        royalty_account_info.key, 
-        amount
-      ), 
+        payer_account_info.key,
+        fee_account_info.key,
+        fee.amount
+      ),
       &[
         payer_account_info.clone(),
-        royalty_account_info.clone(),
+        (*fee_account_info).clone(),
         system_info.clone()
       ]
     )?;
   }
 
-  // recheck initial sale. 
-  msg!("update nft metatdata initial sale if needed");
+  if plan.royalty_vault {
+    // royalties accrue in the vault and are claimed by each holder later
+    for royalty in plan.royalties.iter() {
+      carried.push((royalty.address, royalty.amount));
+    }
+  } else {
+    // for v1 the remaining accounts much be in the same order for the accounts
+    for royalty in plan.royalties.iter() {
+      let royalty_account_info = next_account_info(account_iter)?;
+      if royalty.address != *royalty_account_info.key {
+        return Err(ReeMetaError::RoyaltyAddressInvalid.into())
+      }
+
+      if royalty.amount == 0 {
+        continue;
+      }
+
+      if !is_rent_safe(&rent, royalty_account_info, royalty.amount) {
+        msg!("royalty {} payout of {} lamports carried to the vault", royalty.address.to_string(), royalty.amount);
+        carried.push((royalty.address, royalty.amount));
+        continue;
+      }
+
+      msg!("royalty {} getting {} lamports", royalty.address.to_string(), royalty.amount);
+
+      // pay amount to this user
+      invoke(
+        &system_instruction::transfer(
+          payer_account_info.key, 
+          royalty_account_info.key, 
+          royalty.amount
+        ), 
+        &[
+          payer_account_info.clone(),
+          royalty_account_info.clone(),
+          system_info.clone()
+        ]
+      )?;
+    }
+  }
+
+  msg!("payout to target {}", plan.seller);
+  if plan.seller > 0 {
+    if is_rent_safe(&rent, target_account_info, plan.seller) {
+      invoke(
+        &system_instruction::transfer(
+          payer_account_info.key, 
+          target_account_info.key,
+          plan.seller
+        ),
+        &[
+          payer_account_info.clone(),
+          target_account_info.clone(),
+          system_info.clone()
+        ]
+      )?;
+    } else {
+      msg!("target payout carried to the vault");
+      carried.push((*target_account_info.key, plan.seller));
+    }
+  }
+
+  if carried.iter().any(|(_, amount)| *amount > 0) {
+    // the vault follows the royalty accounts
+    let vault_account_info = next_account_info(account_iter)?;
+    credit_vault(
+      program_id,
+      &metadata.mint,
+      vault_account_info,
+      payer_account_info,
+      system_info,
+      &carried,
+    )?;
+  }
+
+  Ok(())
+}
+
+/// move the NFT from the source token account to the destination, thawing and
+/// re-freezing both with the freeze authority PDA when royalties are enforced
+fn move_nft<'a>(
+  program_id: &Pubkey,
+  metadata: &Metadata<ArtNft>,
+  mint_account_info: &AccountInfo<'a>,
+  freeze_authority_account_info: &AccountInfo<'a>,
+  owner_account_info: &AccountInfo<'a>,
+  source_account_info: &AccountInfo<'a>,
+  destination_account_info: &AccountInfo<'a>,
+  token_program_info: &AccountInfo<'a>,
+) -> ProgramResult {
+  let (mint, source, destination) = assert_nft_transfer(
+    &metadata.mint,
+    mint_account_info,
+    owner_account_info,
+    source_account_info,
+    destination_account_info,
+    token_program_info,
+  )?;
 
+  let (freeze_key, freeze_bump_seed) = find_freeze_authority_address(program_id, &metadata.mint);
+  let freeze_authority_seeds: &[&[u8]] = &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    metadata.mint.as_ref(),
+    FREEZE_PREFIX.as_bytes(),
+    &[freeze_bump_seed]
+  ];
+
+  if metadata.data.enforced {
+    if *freeze_authority_account_info.key != freeze_key {
+      return Err(ReeMetaError::InvalidFreezeAuthority.into())
+    }
+
+    for (account_info, account) in [(source_account_info, &source), (destination_account_info, &destination)] {
+      if account.is_frozen() {
+        invoke_signed(
+          &spl_token_2022::instruction::thaw_account(
+            token_program_info.key,
+            account_info.key,
+            mint_account_info.key,
+            &freeze_key,
+            &[]
+          )?,
+          &[
+            account_info.clone(),
+            mint_account_info.clone(),
+            freeze_authority_account_info.clone(),
+          ],
+          &[freeze_authority_seeds]
+        )?;
+      }
+    }
+  }
+
+  transfer_nft(
+    &mint,
+    mint_account_info,
+    owner_account_info,
+    source_account_info,
+    destination_account_info,
+    token_program_info,
+  )?;
+
+  if metadata.data.enforced {
+    for account_info in [source_account_info, destination_account_info] {
+      invoke_signed(
+        &spl_token_2022::instruction::freeze_account(
+          token_program_info.key,
+          account_info.key,
+          mint_account_info.key,
+          &freeze_key,
+          &[]
+        )?,
+        &[
+          account_info.clone(),
+          mint_account_info.clone(),
+          freeze_authority_account_info.clone(),
+        ],
+        &[freeze_authority_seeds]
+      )?;
+    }
+  }
+
+  Ok(())
+}
+
+pub fn transfer(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  metadata: Metadata<ArtNft>,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let _metadata_account_info = next_account_info(account_iter)?;
+  let update_authority_account_info = next_account_info(account_iter)?;
+  let mint_account_info = next_account_info(account_iter)?;
+  let freeze_authority_account_info = next_account_info(account_iter)?;
+  let owner_account_info = next_account_info(account_iter)?;
+  let source_account_info = next_account_info(account_iter)?;
+  let destination_account_info = next_account_info(account_iter)?;
+  let token_program_info = next_account_info(account_iter)?;
+
+  if metadata.data.soulbound {
+    msg!("soulbound NFTs can not be transferred");
+    return Err(ReeMetaError::SoulboundNft.into())
+  }
+
+  // non enforced NFTs move with the token program directly
+  if !metadata.data.enforced {
+    return Err(ReeMetaError::NotEnforced.into())
+  }
+
+  // a transfer pays no royalties so only the update authority can waive them,
+  // any other move of an enforced NFT goes through a sale
+  assert_update_authority(
+    program_id,
+    &metadata.update_type,
+    &metadata.update_authority,
+    update_authority_account_info,
+    accounts,
+  )?;
+
+  move_nft(
+    program_id,
+    &metadata,
+    mint_account_info,
+    freeze_authority_account_info,
+    owner_account_info,
+    source_account_info,
+    destination_account_info,
+    token_program_info,
+  )
+}
+
+/// a sale moves the NFT from the seller to the buyer and pays the amount out in the
+/// same instruction, so the initial sale only completes with a real transfer
+pub fn nft_transaction(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  mut metadata: Metadata<ArtNft>,
+  data: NftTransactionArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+  let payer_account_info = next_account_info(account_iter)?;
+  let target_account_info = next_account_info(account_iter)?;
+  let system_info = next_account_info(account_iter)?;
+  let mint_account_info = next_account_info(account_iter)?;
+  let freeze_authority_account_info = next_account_info(account_iter)?;
+  let seller_token_account_info = next_account_info(account_iter)?;
+  let buyer_token_account_info = next_account_info(account_iter)?;
+  let token_program_info = next_account_info(account_iter)?;
+
+  if *system_info.key != system_program::ID {
+    msg!("Invalid system account");
+    return Err(ReeMetaError::InvalidInstruction.into())
+  }
+
+  if metadata.data.soulbound {
+    msg!("soulbound NFTs can not be sold");
+    return Err(ReeMetaError::SoulboundNft.into())
+  }
+
+  // a free initial sale would complete it without paying the royalties
+  if data.amount == 0 && !metadata.data.initial_sale {
+    msg!("the initial sale needs an amount");
+    return Err(ReeMetaError::InvalidSaleAmount.into())
+  }
+
+  assert_token_program(token_program_info)?;
+  assert_buyer(
+    payer_account_info,
+    target_account_info,
+    buyer_token_account_info,
+    token_program_info.key,
+  )?;
+
+  // the seller signs for the NFT leaving their token account
+  move_nft(
+    program_id,
+    &metadata,
+    mint_account_info,
+    freeze_authority_account_info,
+    target_account_info,
+    seller_token_account_info,
+    buyer_token_account_info,
+    token_program_info,
+  )?;
+
+  // the protocol fee recipient is found by key like the config
+  let config = load_program_config(program_id, accounts)?;
+  let protocol_fee = match config.protocol_fee {
+    0 => None,
+    basis_points => match accounts.iter().find(|account| *account.key == config.fee_recipient) {
+      None => {
+        msg!("protocol fee recipient missing");
+        return Err(ReeMetaError::InvalidConfig.into())
+      },
+      Some(fee_recipient_account_info) => Some((fee_recipient_account_info, basis_points)),
+    },
+  };
+
+  pay_out(
+    program_id,
+    &metadata,
+    payer_account_info,
+    target_account_info,
+    system_info,
+    account_iter,
+    protocol_fee,
+    &data,
+  )?;
+
+  msg!("update nft metatdata initial sale if needed");
   if !metadata.data.initial_sale {
     // initial sale all went to royalty. change initial sale to true
     metadata.data.initial_sale = true;
+
+    resize_account(
+      metadata_account_info,
+      payer_account_info,
+      system_info,
+      &Rent::get()?,
+      metadata.size(),
+    )?;
+
     metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
-    msg!("done");
-    return Ok(())
   }
 
-  msg!("payout to target {}", target_payout);
-  if target_payout > 0 {
-    invoke(
-      &system_instruction::transfer(
-        payer_account_info.key, 
-        target_account_info.key,
-        target_payout
-      ),
+  Ok(())
+}
+
+/// burn a soulbound NFT from its holder for the update authority. the holder account is
+/// thawed by the freeze authority PDA which also burns as the permanent delegate of the mint
+pub fn revoke(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  metadata: Metadata<ArtNft>,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let _metadata_account_info = next_account_info(account_iter)?;
+  let update_authority_account_info = next_account_info(account_iter)?;
+  let mint_account_info = next_account_info(account_iter)?;
+  let freeze_authority_account_info = next_account_info(account_iter)?;
+  let token_account_info = next_account_info(account_iter)?;
+  let token_program_info = next_account_info(account_iter)?;
+
+  if !metadata.data.soulbound {
+    msg!("only soulbound NFTs can be revoked");
+    return Err(ReeMetaError::InvalidNFTKind.into())
+  }
+
+  assert_update_authority(
+    program_id,
+    &metadata.update_type,
+    &metadata.update_authority,
+    update_authority_account_info,
+    accounts,
+  )?;
+
+  if *mint_account_info.key != metadata.mint {
+    msg!("mint does not match the metadata");
+    return Err(ReeMetaError::InvalidTokenAccount.into())
+  }
+
+  let (freeze_key, freeze_bump_seed) = find_freeze_authority_address(program_id, &metadata.mint);
+  if *freeze_authority_account_info.key != freeze_key {
+    return Err(ReeMetaError::InvalidFreezeAuthority.into())
+  }
+
+  assert_permanent_delegate(mint_account_info, token_program_info, &freeze_key)?;
+
+  let token_account: TokenAccount = unpack_token_account(token_account_info, token_program_info.key)?;
+  if token_account.mint != metadata.mint || token_account.amount == 0 {
+    return Err(ReeMetaError::InvalidTokenAccount.into())
+  }
+
+  let freeze_authority_seeds: &[&[u8]] = &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    metadata.mint.as_ref(),
+    FREEZE_PREFIX.as_bytes(),
+    &[freeze_bump_seed]
+  ];
+
+  if token_account.is_frozen() {
+    invoke_signed(
+      &spl_token_2022::instruction::thaw_account(
+        token_program_info.key,
+        token_account_info.key,
+        mint_account_info.key,
+        &freeze_key,
+        &[]
+      )?,
       &[
-        payer_account_info.clone(),
-        target_account_info.clone(),
-        system_info.clone()
-      ]
+        token_account_info.clone(),
+        mint_account_info.clone(),
+        freeze_authority_account_info.clone(),
+      ],
+      &[freeze_authority_seeds]
     )?;
   }
 
+  msg!("revoking {} from {}", metadata.mint.to_string(), token_account.owner.to_string());
+  invoke_signed(
+    &spl_token_2022::instruction::burn(
+      token_program_info.key,
+      token_account_info.key,
+      mint_account_info.key,
+      &freeze_key,
+      &[],
+      token_account.amount
+    )?,
+    &[
+      token_account_info.clone(),
+      mint_account_info.clone(),
+      freeze_authority_account_info.clone(),
+    ],
+    &[freeze_authority_seeds]
+  )?;
+
   Ok(())
-}
\ No newline at end of file
+}
+
+/// convert the royalty share of the signing holder into fractions of a fungible mint. the
+/// distributor PDA takes the place of the holder in the royalties and mints the fractions
+pub fn fractionalize(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  mut metadata: Metadata<ArtNft>,
+  data: FractionalizeArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+  let payer_account_info = next_account_info(account_iter)?;
+  let holder_account_info = next_account_info(account_iter)?;
+  let distributor_account_info = next_account_info(account_iter)?;
+  let fraction_mint_account_info = next_account_info(account_iter)?;
+  let holder_token_account_info = next_account_info(account_iter)?;
+  let system_info = next_account_info(account_iter)?;
+  let rent_info = next_account_info(account_iter)?;
+  let token_program_info = next_account_info(account_iter)?;
+
+  if *system_info.key != system_program::ID || *rent_info.key != RENT_ID {
+    return Err(ReeMetaError::InvalidInstruction.into())
+  }
+  assert_token_program(token_program_info)?;
+
+  if data.supply == 0 {
+    msg!("fraction supply must not be zero");
+    return Err(ReeMetaError::InvalidFractionMint.into())
+  }
+
+  let index = match metadata.data.royalties.iter()
+    .position(|royalty| royalty.address == *holder_account_info.key) {
+      None => return Err(ReeMetaError::RoyaltyAddressInvalid.into()),
+      Some(index) => index,
+    };
+
+  let (distributor_key, distributor_bump_seed) = find_distributor_address(
+    program_id,
+    &metadata.mint,
+    holder_account_info.key,
+  );
+  if distributor_key != *distributor_account_info.key {
+    return Err(ReeMetaError::InvalidDistributor.into())
+  }
+
+  let fraction_mint: Mint = unpack_mint(fraction_mint_account_info, token_program_info.key)?;
+  if fraction_mint.supply != 0 || fraction_mint.mint_authority != COption::Some(distributor_key) {
+    msg!("fraction mint needs no supply and the distributor as mint authority");
+    return Err(ReeMetaError::InvalidFractionMint.into())
+  }
+  // a freeze authority could lock staked or unstaked fractions out of their payouts
+  if fraction_mint.freeze_authority.is_some() {
+    msg!("fraction mint must not have a freeze authority");
+    return Err(ReeMetaError::InvalidFractionMint.into())
+  }
+
+  let holder_token_account: TokenAccount = unpack_token_account(holder_token_account_info, token_program_info.key)?;
+  if holder_token_account.mint != *fraction_mint_account_info.key {
+    return Err(ReeMetaError::InvalidTokenAccount.into())
+  }
+
+  let distributor_seeds: &[&[u8]] = &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    metadata.mint.as_ref(),
+    DISTRIBUTOR_PREFIX.as_bytes(),
+    holder_account_info.key.as_ref(),
+    &[distributor_bump_seed]
+  ];
+
+  let rent = &Rent::from_account_info(rent_info)?;
+  create_pda_account(
+    payer_account_info,
+    distributor_account_info,
+    system_info,
+    program_id,
+    rent,
+    FractionDistributor::size(),
+    distributor_seeds,
+  )?;
+
+  let distributor = FractionDistributor{
+    is_initialized: true,
+    nft_mint: metadata.mint,
+    holder: *holder_account_info.key,
+    fraction_mint: *fraction_mint_account_info.key,
+    supply: data.supply,
+    total_staked: 0,
+    reward_per_fraction: 0,
+    tracked: 0,
+    reserved: 0,
+  };
+
+  msg!("minting {} fractions of a {} share", data.supply, metadata.data.royalties[index].share);
+  invoke_signed(
+    &spl_token_2022::instruction::mint_to(
+      token_program_info.key,
+      fraction_mint_account_info.key,
+      holder_token_account_info.key,
+      &distributor_key,
+      &[],
+      data.supply
+    )?,
+    &[
+      fraction_mint_account_info.clone(),
+      holder_token_account_info.clone(),
+      distributor_account_info.clone(),
+    ],
+    &[distributor_seeds]
+  )?;
+
+  // the supply is fixed from now on
+  invoke_signed(
+    &spl_token_2022::instruction::set_authority(
+      token_program_info.key,
+      fraction_mint_account_info.key,
+      None,
+      spl_token_2022::instruction::AuthorityType::MintTokens,
+      &distributor_key,
+      &[]
+    )?,
+    &[
+      fraction_mint_account_info.clone(),
+      distributor_account_info.clone(),
+    ],
+    &[distributor_seeds]
+  )?;
+
+  metadata.data.royalties[index].address = distributor_key;
+  // the initial sale pays the holder through the primary split, that moves over as well
+  for split in metadata.data.primary_split.iter_mut()
+    .filter(|split| split.address == *holder_account_info.key) {
+    split.address = distributor_key;
+  }
+  metadata.data.validate()?;
+
+  resize_account(
+    metadata_account_info,
+    payer_account_info,
+    system_info,
+    rent,
+    metadata.size(),
+  )?;
+
+  distributor.serialize(&mut *distributor_account_info.data.borrow_mut())?;
+  metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
+  Ok(())
+}
+
+impl NftKind for ArtNft {
+  const KIND: Kind = Kind::RoyaltyArt;
+  const NAME: &'static str = "RoyaltyArt";
+
+  fn create(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: CreateMetadataArgs,
+  ) -> ProgramResult {
+    create(program_id, accounts, data)
+  }
+
+  fn add_royalty(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata: Metadata<Self>,
+    data: AddRoyaltyArgs,
+  ) -> ProgramResult {
+    add_royalty(program_id, accounts, metadata, data)
+  }
+
+  fn set_primary_split(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata: Metadata<Self>,
+    data: SetPrimarySplitArgs,
+  ) -> ProgramResult {
+    set_primary_split(program_id, accounts, metadata, data)
+  }
+
+  fn create_royalty_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata: Metadata<Self>,
+  ) -> ProgramResult {
+    create_royalty_vault(program_id, accounts, metadata)
+  }
+
+  fn quote_royalty(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata: Metadata<Self>,
+    data: QuoteRoyaltyArgs,
+  ) -> ProgramResult {
+    quote_royalty(program_id, accounts, metadata, data)
+  }
+
+  fn nft_transaction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata: Metadata<Self>,
+    data: NftTransactionArgs,
+  ) -> ProgramResult {
+    nft_transaction(program_id, accounts, metadata, data)
+  }
+
+  fn transfer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata: Metadata<Self>,
+  ) -> ProgramResult {
+    transfer(program_id, accounts, metadata)
+  }
+
+  fn set_attribute(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata: Metadata<Self>,
+    data: SetAttributeArgs,
+  ) -> ProgramResult {
+    set_attribute(program_id, accounts, metadata, data)
+  }
+
+  fn remove_attribute(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata: Metadata<Self>,
+    data: RemoveAttributeArgs,
+  ) -> ProgramResult {
+    remove_attribute(program_id, accounts, metadata, data)
+  }
+
+  fn update_uri(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata: Metadata<Self>,
+    data: UpdateUriArgs,
+  ) -> ProgramResult {
+    update_uri(program_id, accounts, metadata, data)
+  }
+
+  fn revoke(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata: Metadata<Self>,
+  ) -> ProgramResult {
+    revoke(program_id, accounts, metadata)
+  }
+
+  fn fractionalize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata: Metadata<Self>,
+    data: FractionalizeArgs,
+  ) -> ProgramResult {
+    fractionalize(program_id, accounts, metadata, data)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::state::{Kind, UpdateType};
+
+  struct TestAccount {
+    key: Pubkey,
+    signer: bool,
+    lamports: u64,
+    data: Vec<u8>,
+    owner: Pubkey,
+  }
+
+  impl TestAccount {
+    fn new(key: Pubkey, signer: bool) -> Self {
+      TestAccount{ key, signer, lamports: 0, data: Vec::new(), owner: system_program::ID }
+    }
+
+    fn info(&mut self) -> AccountInfo<'_> {
+      AccountInfo::new(&self.key, self.signer, true, &mut self.lamports, &mut self.data, &self.owner, false, 0)
+    }
+  }
+
+  fn soulbound_nft(update_authority: Pubkey) -> Metadata<ArtNft> {
+    Metadata{
+      kind: Kind::RoyaltyArt,
+      mint: Pubkey::new_unique(),
+      is_modifiable: true,
+      update_type: UpdateType::WalletSigner,
+      collection: None,
+      update_authority: Some(update_authority),
+      data: ArtNft{
+        name: "badge".to_string(),
+        symbol: "BDG".to_string(),
+        uri: "https://example.com/badge.json".to_string(),
+        resale_fee: 0,
+        initial_sale: false,
+        collection: None,
+        royalties: vec![Royalty{ address: update_authority, share: 100, verified: true }],
+        royalty_vault: false,
+        primary_split: Vec::new(),
+        enforced: false,
+        attributes: Vec::new(),
+        soulbound: true,
+      },
+    }
+  }
+
+  // metadata, update authority, mint, freeze authority, holder token account, token program
+  fn revoke_accounts(program_id: &Pubkey, metadata: &Metadata<ArtNft>) -> Vec<TestAccount> {
+    let (freeze_key, _) = find_freeze_authority_address(program_id, &metadata.mint);
+    vec![
+      TestAccount::new(Pubkey::new_unique(), false),
+      TestAccount::new(metadata.update_authority.unwrap(), true),
+      TestAccount::new(metadata.mint, false),
+      TestAccount::new(freeze_key, false),
+      TestAccount::new(Pubkey::new_unique(), false),
+      TestAccount::new(spl_token_2022::ID, false),
+    ]
+  }
+
+  fn revoke_with(
+    program_id: &Pubkey,
+    metadata: Metadata<ArtNft>,
+    accounts: &mut [TestAccount],
+  ) -> ProgramResult {
+    let infos: Vec<AccountInfo> = accounts.iter_mut().map(|account| account.info()).collect();
+    revoke(program_id, &infos, metadata)
+  }
+
+  #[test]
+  fn test_revoke_only_soulbound() {
+    let program_id = Pubkey::new_unique();
+    let mut metadata = soulbound_nft(Pubkey::new_unique());
+    metadata.data.soulbound = false;
+    let mut accounts = revoke_accounts(&program_id, &metadata);
+    assert_eq!(revoke_with(&program_id, metadata, &mut accounts), Err(ReeMetaError::InvalidNFTKind.into()));
+  }
+
+  #[test]
+  fn test_revoke_by_the_update_authority_only() {
+    let program_id = Pubkey::new_unique();
+    let metadata = soulbound_nft(Pubkey::new_unique());
+
+    let mut accounts = revoke_accounts(&program_id, &metadata);
+    accounts[1].key = Pubkey::new_unique();
+    assert_eq!(
+      revoke_with(&program_id, metadata.clone(), &mut accounts),
+      Err(ReeMetaError::InvalidUpdateAuthority.into())
+    );
+
+    let mut accounts = revoke_accounts(&program_id, &metadata);
+    accounts[1].signer = false;
+    assert_eq!(
+      revoke_with(&program_id, metadata, &mut accounts),
+      Err(ReeMetaError::InvalidUpdateAuthority.into())
+    );
+  }
+
+  #[test]
+  fn test_revoke_wrong_mint_or_freeze_authority() {
+    let program_id = Pubkey::new_unique();
+    let metadata = soulbound_nft(Pubkey::new_unique());
+
+    let mut accounts = revoke_accounts(&program_id, &metadata);
+    accounts[2].key = Pubkey::new_unique();
+    assert_eq!(
+      revoke_with(&program_id, metadata.clone(), &mut accounts),
+      Err(ReeMetaError::InvalidTokenAccount.into())
+    );
+
+    let mut accounts = revoke_accounts(&program_id, &metadata);
+    accounts[3].key = Pubkey::new_unique();
+    assert_eq!(
+      revoke_with(&program_id, metadata, &mut accounts),
+      Err(ReeMetaError::InvalidFreezeAuthority.into())
+    );
+  }
+}
@@ -0,0 +1,54 @@
+//! decode a ree-meta metadata account without a node and print it as JSON.
+//! reads the raw account bytes (`solana account <ADDRESS> --output-file <FILE>`) or the
+//! JSON of `solana account <ADDRESS> --output json`, from a file or stdin.
+use std::{
+  error::Error,
+  io::Read,
+  process::exit,
+};
+
+use ree_meta::decode::decode_metadata_json;
+
+/// the base64 data of `solana account --output json`, "data": ["<base64>", "base64"]
+fn account_json_data(json: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+  let data = json.find("\"data\"")
+    .map(|start| &json[start + "\"data\"".len()..])
+    .ok_or("no data field in the account JSON")?;
+  let encoded = data.trim_start()
+    .strip_prefix(':').map(str::trim_start)
+    .and_then(|data| data.strip_prefix('['))
+    .map(str::trim_start)
+    .and_then(|data| data.strip_prefix('"'))
+    .and_then(|data| data.split('"').next())
+    .ok_or("account JSON data is not a [\"<data>\", \"base64\"] pair")?;
+
+  Ok(base64::decode(encoded)?)
+}
+
+fn run() -> Result<String, Box<dyn Error>> {
+  let mut input: Vec<u8> = Vec::new();
+  match std::env::args().nth(1) {
+    None => { std::io::stdin().read_to_end(&mut input)?; },
+    Some(path) if path == "-h" || path == "--help" => {
+      return Ok("usage: ree-meta-decode [FILE]\nreads raw account bytes or `solana account --output json` from FILE or stdin".to_string())
+    },
+    Some(path) => { input = std::fs::read(path)?; },
+  }
+
+  let data = match input.iter().find(|byte| !byte.is_ascii_whitespace()) {
+    Some(b'{') => account_json_data(std::str::from_utf8(&input)?)?,
+    _ => input,
+  };
+
+  Ok(decode_metadata_json(&data)?)
+}
+
+fn main() {
+  match run() {
+    Ok(output) => println!("{}", output),
+    Err(err) => {
+      eprintln!("error: {}", err);
+      exit(1);
+    },
+  }
+}
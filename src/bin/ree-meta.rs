@@ -0,0 +1,338 @@
+//! ree-meta command line tool, builds ree-meta transactions with the instruction helpers
+//! and sends them to an RPC node. run against a local `solana-test-validator` with
+//! `--url http://127.0.0.1:8899`.
+use std::error::Error;
+
+use clap::{Parser, Subcommand};
+use solana_client::rpc_client::RpcClient;
+use solana_program::{
+  borsh::try_from_slice_unchecked,
+  instruction::{AccountMeta, Instruction},
+  program_option::COption,
+  program_pack::Pack,
+  pubkey::Pubkey,
+};
+use solana_sdk::{
+  commitment_config::CommitmentConfig,
+  signature::{read_keypair_file, Keypair, Signer},
+  transaction::Transaction,
+};
+
+use ree_meta::{
+  instruction::{
+    self,
+    CreateMetadataArgs,
+    MetadataArgs,
+    MetadataArgsRRA,
+    NftTransactionArgs,
+    SaleFee,
+  },
+  pda::{
+    find_metadata_address,
+    find_royalty_vault_address,
+    find_freeze_authority_address,
+    find_config_address,
+  },
+  state::{ArtNft, CustomNft, GameItem, Kind, Metadata, ProgramConfig, UpdateType},
+};
+
+type CliResult<T> = Result<T, Box<dyn Error>>;
+
+#[derive(Parser)]
+#[clap(name = "ree-meta", version, about = "ree-meta NFT metadata operations")]
+struct Cli {
+  /// RPC URL of the cluster
+  #[clap(long, short = 'u', global = true, default_value = "http://127.0.0.1:8899")]
+  url: String,
+
+  /// keypair file of the payer and default authority
+  #[clap(long, short = 'k', global = true, default_value = "~/.config/solana/id.json")]
+  keypair: String,
+
+  /// ree-meta program id
+  #[clap(long, global = true)]
+  program_id: Option<Pubkey>,
+
+  #[clap(subcommand)]
+  command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+  /// create the ArtNFT metadata of a mint, the keypair has to be the mint authority
+  Create {
+    #[clap(long)]
+    mint: Pubkey,
+    #[clap(long)]
+    name: String,
+    #[clap(long)]
+    symbol: String,
+    #[clap(long)]
+    uri: String,
+    /// resale fee in whole percent
+    #[clap(long, default_value = "0")]
+    resale_fee: u16,
+    /// holder of all royalty shares, defaults to the keypair
+    #[clap(long)]
+    royalty_owner: Option<Pubkey>,
+    /// who can mint the one token, defaults to the keypair
+    #[clap(long)]
+    new_mint_authority: Option<Pubkey>,
+    /// keep the metadata modifiable with the keypair as update authority
+    #[clap(long)]
+    mutable: bool,
+    /// enforce royalties with the freeze authority PDA
+    #[clap(long)]
+    enforced: bool,
+    /// never transferable, the update authority can revoke it when --mutable. needs a
+    /// Token-2022 mint with the freeze authority PDA as permanent delegate
+    #[clap(long)]
+    soulbound: bool,
+    #[clap(long)]
+    collection: Option<Pubkey>,
+    #[clap(long, default_value_t = spl_token::ID)]
+    token_program: Pubkey,
+  },
+  /// mint the one token of an NFT to a token account and close the mint
+  Mint {
+    #[clap(long)]
+    mint: Pubkey,
+    /// token account receiving the NFT
+    #[clap(long)]
+    recipient: Pubkey,
+    #[clap(long, default_value_t = spl_token::ID)]
+    token_program: Pubkey,
+  },
+  /// lock the metadata against further changes
+  Lock {
+    #[clap(long)]
+    mint: Pubkey,
+  },
+  /// give part of the first royalty's share to a new royalty
+  AddRoyalty {
+    #[clap(long)]
+    mint: Pubkey,
+    #[clap(long)]
+    royalty: Pubkey,
+    #[clap(long)]
+    share: u16,
+  },
+  /// buy the NFT, paying the amount out to the fees, royalties and seller
+  Pay {
+    #[clap(long)]
+    mint: Pubkey,
+    /// sale amount in lamports
+    #[clap(long)]
+    amount: u64,
+    /// keypair file of the seller, who signs for the NFT leaving their token account
+    #[clap(long)]
+    seller_keypair: String,
+    #[clap(long)]
+    seller_token_account: Pubkey,
+    #[clap(long)]
+    buyer_token_account: Pubkey,
+    #[clap(long, requires = "marketplace-fee")]
+    marketplace: Option<Pubkey>,
+    /// marketplace fee in basis points
+    #[clap(long, requires = "marketplace")]
+    marketplace_fee: Option<u16>,
+    #[clap(long, requires = "referral-fee")]
+    referrer: Option<Pubkey>,
+    /// referral fee in basis points
+    #[clap(long, requires = "referrer")]
+    referral_fee: Option<u16>,
+    #[clap(long, default_value_t = spl_token::ID)]
+    token_program: Pubkey,
+  },
+  /// decode and print the metadata of a mint
+  Show {
+    #[clap(long)]
+    mint: Pubkey,
+  },
+}
+
+fn expand_home(path: &str) -> String {
+  match (path.strip_prefix("~/"), std::env::var("HOME")) {
+    (Some(rest), Ok(home)) => format!("{}/{}", home, rest),
+    _ => path.to_string(),
+  }
+}
+
+fn load_keypair(path: &str) -> CliResult<Keypair> {
+  read_keypair_file(expand_home(path))
+    .map_err(|err| format!("could not read keypair {}: {}", path, err).into())
+}
+
+fn send(client: &RpcClient, instructions: &[Instruction], payer: &Keypair, signers: &[&Keypair]) -> CliResult<()> {
+  let blockhash = client.get_latest_blockhash()?;
+  let transaction = Transaction::new_signed_with_payer(
+    instructions,
+    Some(&payer.pubkey()),
+    signers,
+    blockhash,
+  );
+  let signature = client.send_and_confirm_transaction(&transaction)?;
+  println!("signature: {}", signature);
+  Ok(())
+}
+
+fn fetch_art_nft(client: &RpcClient, program_id: &Pubkey, mint: &Pubkey) -> CliResult<Metadata<ArtNft>> {
+  let (metadata_key, _) = find_metadata_address(program_id, mint);
+  let data = client.get_account_data(&metadata_key)?;
+  if data.first().map(Kind::from) != Some(Kind::RoyaltyArt) {
+    return Err(format!("metadata {} is not an ArtNFT", metadata_key).into())
+  }
+  Ok(Metadata::from_slice(&data)?)
+}
+
+fn fetch_config(client: &RpcClient, program_id: &Pubkey) -> CliResult<ProgramConfig> {
+  let (config_key, _) = find_config_address(program_id);
+  match client.get_account_data(&config_key) {
+    Ok(data) if !data.is_empty() => Ok(try_from_slice_unchecked(&data)?),
+    _ => Ok(ProgramConfig::default()),
+  }
+}
+
+fn main() -> CliResult<()> {
+  let cli = Cli::parse();
+  let program_id = cli.program_id.unwrap_or_else(ree_meta::id);
+  let client = RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed());
+
+  match cli.command {
+    Command::Create {
+      mint, name, symbol, uri, resale_fee, royalty_owner, new_mint_authority,
+      mutable, enforced, soulbound, collection, token_program,
+    } => {
+      let payer = load_keypair(&cli.keypair)?;
+      let payer_key = payer.pubkey();
+      let (metadata_key, _) = find_metadata_address(&program_id, &mint);
+      let update_type = if mutable { UpdateType::WalletSigner } else { UpdateType::None };
+
+      let args = CreateMetadataArgs{
+        metadata: MetadataArgs{
+          kind: Kind::RoyaltyArt,
+          is_modifiable: mutable,
+          update_type,
+          in_collection: collection.is_some(),
+        },
+        aar_data: MetadataArgsRRA{ name, symbol, uri, resale_fee, enforced, soulbound },
+      };
+
+      let ix = instruction::create_metadata(
+        &program_id,
+        &token_program,
+        &metadata_key,
+        &mint,
+        &royalty_owner.unwrap_or(payer_key),
+        &payer_key,
+        &new_mint_authority.unwrap_or(payer_key),
+        &payer_key,
+        if mutable { Some(&payer_key) } else { None },
+        collection.as_ref(),
+        args,
+      );
+      println!("metadata: {}", metadata_key);
+      send(&client, &[ix], &payer, &[&payer])
+    },
+    Command::Mint { mint, recipient, token_program } => {
+      let payer = load_keypair(&cli.keypair)?;
+      let mut ix = instruction::mint_nft(&program_id, &token_program, &mint, &payer.pubkey(), &recipient);
+
+      // enforced mints freeze the recipient with the freeze authority PDA
+      let mint_data = client.get_account_data(&mint)?;
+      let mint_state = spl_token::state::Mint::unpack_from_slice(&mint_data[..spl_token::state::Mint::LEN])?;
+      let (freeze_key, _) = find_freeze_authority_address(&program_id, &mint);
+      if mint_state.freeze_authority == COption::Some(freeze_key) {
+        ix.accounts.push(AccountMeta::new_readonly(freeze_key, false));
+      }
+
+      send(&client, &[ix], &payer, &[&payer])
+    },
+    Command::Lock { mint } => {
+      let payer = load_keypair(&cli.keypair)?;
+      let (metadata_key, _) = find_metadata_address(&program_id, &mint);
+      let ix = instruction::lock_nft(&program_id, &metadata_key, &payer.pubkey(), &payer.pubkey());
+      send(&client, &[ix], &payer, &[&payer])
+    },
+    Command::AddRoyalty { mint, royalty, share } => {
+      let payer = load_keypair(&cli.keypair)?;
+      let (metadata_key, _) = find_metadata_address(&program_id, &mint);
+      let metadata = fetch_art_nft(&client, &program_id, &mint)?;
+      let first_royalty = metadata.data.royalties.first()
+        .map(|royalty| royalty.address)
+        .ok_or("the NFT has no royalties")?;
+      let ix = instruction::add_royalty(
+        &program_id, &metadata_key, &payer.pubkey(), &payer.pubkey(), &royalty, &first_royalty, share
+      );
+      send(&client, &[ix], &payer, &[&payer])
+    },
+    Command::Pay {
+      mint, amount, seller_keypair, seller_token_account, buyer_token_account,
+      marketplace, marketplace_fee, referrer, referral_fee, token_program,
+    } => {
+      let payer = load_keypair(&cli.keypair)?;
+      let seller = load_keypair(&seller_keypair)?;
+      let (metadata_key, _) = find_metadata_address(&program_id, &mint);
+      let metadata = fetch_art_nft(&client, &program_id, &mint)?;
+      let config = fetch_config(&client, &program_id)?;
+
+      // royalty accounts are paid directly unless the vault collects them,
+      // the vault is passed either way to take payouts that are carried forward
+      let mut royalties: Vec<Pubkey> = Vec::new();
+      if !metadata.data.royalty_vault {
+        royalties.extend(metadata.data.sale_recipients().iter().map(|royalty| royalty.address));
+      }
+      let (vault_key, _) = find_royalty_vault_address(&program_id, &mint);
+
+      let protocol_fee_recipient = match config.is_initialized && config.protocol_fee > 0 {
+        true => Some(config.fee_recipient),
+        false => None,
+      };
+
+      let args = NftTransactionArgs{
+        amount,
+        marketplace_fee: marketplace.zip(marketplace_fee)
+          .map(|(recipient, basis_points)| SaleFee{ recipient, basis_points }),
+        referral_fee: referrer.zip(referral_fee)
+          .map(|(recipient, basis_points)| SaleFee{ recipient, basis_points }),
+      };
+
+      let ix = instruction::nft_funding_sol(
+        &program_id,
+        &token_program,
+        &metadata_key,
+        &payer.pubkey(),
+        &seller.pubkey(),
+        &mint,
+        &find_freeze_authority_address(&program_id, &mint).0,
+        &seller_token_account,
+        &buyer_token_account,
+        royalties,
+        Some(&vault_key),
+        protocol_fee_recipient.as_ref(),
+        args,
+      );
+      send(&client, &[ix], &payer, &[&payer, &seller])
+    },
+    Command::Show { mint } => {
+      let (metadata_key, _) = find_metadata_address(&program_id, &mint);
+      let data = client.get_account_data(&metadata_key)?;
+      println!("metadata: {}", metadata_key);
+      match data.first().map(Kind::from) {
+        Some(Kind::RoyaltyArt) => {
+          let metadata = Metadata::<ArtNft>::from_slice(&data)?;
+          println!("{:#?}", metadata);
+        },
+        Some(Kind::GameItem) => {
+          let metadata = Metadata::<GameItem>::from_slice(&data)?;
+          println!("{:#?}", metadata);
+        },
+        _ => {
+          let metadata = Metadata::<CustomNft>::from_slice(&data)?;
+          println!("{:#?}", metadata);
+        },
+      }
+      Ok(())
+    },
+  }
+}
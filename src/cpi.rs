@@ -0,0 +1,218 @@
+//! wrappers for calling ree-meta from another program. each accounts struct is laid out in
+//! the order documented on ReeMetadataInstruction, the instruction is built by the matching
+//! builder in instruction.rs so the account metas and the account infos always line up
+
+use crate::instruction::{
+  self,
+  CreateMetadataArgs,
+  NftTransactionArgs,
+};
+
+use solana_program::{
+  account_info::AccountInfo,
+  entrypoint::ProgramResult,
+  instruction::{AccountMeta, Instruction},
+  program::{invoke, invoke_signed},
+};
+
+/// accounts of CreateMetaData
+pub struct CreateMetadata<'a, 'info> {
+  pub program: &'a AccountInfo<'info>,
+  pub metadata: &'a AccountInfo<'info>,
+  pub mint: &'a AccountInfo<'info>,
+  pub royalty_owner: &'a AccountInfo<'info>,
+  pub mint_authority: &'a AccountInfo<'info>,
+  pub new_mint_authority: &'a AccountInfo<'info>,
+  pub payer: &'a AccountInfo<'info>,
+  pub update_authority: Option<&'a AccountInfo<'info>>, // unless the update type is None
+  pub system_program: &'a AccountInfo<'info>,
+  pub rent: &'a AccountInfo<'info>,
+  pub token_program: &'a AccountInfo<'info>,
+  pub collection: Option<&'a AccountInfo<'info>>,
+  pub config: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> CreateMetadata<'a, 'info> {
+  pub fn instruction(&self, data: CreateMetadataArgs) -> Instruction {
+    instruction::create_metadata(
+      self.program.key,
+      self.token_program.key,
+      self.metadata.key,
+      self.mint.key,
+      self.royalty_owner.key,
+      self.mint_authority.key,
+      self.new_mint_authority.key,
+      self.payer.key,
+      self.update_authority.map(|info| info.key),
+      self.collection.map(|info| info.key),
+      data,
+    )
+  }
+
+  pub fn account_infos(&self) -> Vec<AccountInfo<'info>> {
+    let mut infos = vec![
+      self.metadata.clone(),
+      self.mint.clone(),
+      self.royalty_owner.clone(),
+      self.mint_authority.clone(),
+      self.new_mint_authority.clone(),
+      self.payer.clone(),
+    ];
+    if let Some(update_authority) = self.update_authority {
+      infos.push(update_authority.clone());
+    }
+    infos.push(self.system_program.clone());
+    infos.push(self.rent.clone());
+    infos.push(self.token_program.clone());
+    if let Some(collection) = self.collection {
+      infos.push(collection.clone());
+    }
+    infos.push(self.config.clone());
+    infos.push(self.program.clone());
+    infos
+  }
+}
+
+/// accounts of MintNFT
+pub struct MintNft<'a, 'info> {
+  pub program: &'a AccountInfo<'info>,
+  pub mint: &'a AccountInfo<'info>,
+  pub mint_authority: &'a AccountInfo<'info>,
+  pub recipient_token_account: &'a AccountInfo<'info>,
+  pub token_program: &'a AccountInfo<'info>,
+  pub freeze_authority: Option<&'a AccountInfo<'info>>, // when royalties are enforced or soulbound
+}
+
+impl<'a, 'info> MintNft<'a, 'info> {
+  pub fn instruction(&self) -> Instruction {
+    let mut ix = instruction::mint_nft(
+      self.program.key,
+      self.token_program.key,
+      self.mint.key,
+      self.mint_authority.key,
+      self.recipient_token_account.key,
+    );
+    // the builder leaves out the optional freeze authority PDA
+    if let Some(freeze_authority) = self.freeze_authority {
+      ix.accounts.push(AccountMeta::new_readonly(*freeze_authority.key, false));
+    }
+    ix
+  }
+
+  pub fn account_infos(&self) -> Vec<AccountInfo<'info>> {
+    let mut infos = vec![
+      self.mint.clone(),
+      self.mint_authority.clone(),
+      self.recipient_token_account.clone(),
+      self.token_program.clone(),
+    ];
+    if let Some(freeze_authority) = self.freeze_authority {
+      infos.push(freeze_authority.clone());
+    }
+    infos.push(self.program.clone());
+    infos
+  }
+}
+
+/// accounts of NftTransaction
+pub struct NftTransaction<'a, 'info> {
+  pub program: &'a AccountInfo<'info>,
+  pub metadata: &'a AccountInfo<'info>,
+  pub payer: &'a AccountInfo<'info>,
+  pub target: &'a AccountInfo<'info>,
+  pub system_program: &'a AccountInfo<'info>,
+  pub mint: &'a AccountInfo<'info>,
+  pub freeze_authority: &'a AccountInfo<'info>,
+  pub seller_token_account: &'a AccountInfo<'info>,
+  pub buyer_token_account: &'a AccountInfo<'info>,
+  pub token_program: &'a AccountInfo<'info>,
+  pub fee_recipients: Vec<&'a AccountInfo<'info>>, // marketplace then referral, as set in the args
+  pub royalties: Vec<&'a AccountInfo<'info>>, // in the order of the sale recipients, empty with a vault
+  pub vault: Option<&'a AccountInfo<'info>>, // when royalties accrue or a payout is carried
+  pub config: &'a AccountInfo<'info>,
+  pub protocol_fee_recipient: Option<&'a AccountInfo<'info>>, // when the config charges a fee
+}
+
+impl<'a, 'info> NftTransaction<'a, 'info> {
+  pub fn instruction(&self, data: NftTransactionArgs) -> Instruction {
+    let royalties = self.royalties.iter()
+      .map(|info| *info.key)
+      .collect();
+
+    instruction::nft_funding_sol(
+      self.program.key,
+      self.token_program.key,
+      self.metadata.key,
+      self.payer.key,
+      self.target.key,
+      self.mint.key,
+      self.freeze_authority.key,
+      self.seller_token_account.key,
+      self.buyer_token_account.key,
+      royalties,
+      self.vault.map(|info| info.key),
+      self.protocol_fee_recipient.map(|info| info.key),
+      data,
+    )
+  }
+
+  pub fn account_infos(&self) -> Vec<AccountInfo<'info>> {
+    let mut infos = vec![
+      self.metadata.clone(),
+      self.payer.clone(),
+      self.target.clone(),
+      self.system_program.clone(),
+      self.mint.clone(),
+      self.freeze_authority.clone(),
+      self.seller_token_account.clone(),
+      self.buyer_token_account.clone(),
+      self.token_program.clone(),
+    ];
+    infos.extend(self.fee_recipients.iter().map(|info| (*info).clone()));
+    infos.extend(self.royalties.iter().map(|info| (*info).clone()));
+    if let Some(vault) = self.vault {
+      infos.push(vault.clone());
+    }
+    infos.push(self.config.clone());
+    if let Some(protocol_fee_recipient) = self.protocol_fee_recipient {
+      infos.push(protocol_fee_recipient.clone());
+    }
+    infos.push(self.program.clone());
+    infos
+  }
+}
+
+pub fn create_metadata(accounts: &CreateMetadata, data: CreateMetadataArgs) -> ProgramResult {
+  invoke(&accounts.instruction(data), &accounts.account_infos())
+}
+
+/// create metadata with a PDA of the calling program as mint authority or payer
+pub fn create_metadata_signed(
+  accounts: &CreateMetadata,
+  data: CreateMetadataArgs,
+  signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+  invoke_signed(&accounts.instruction(data), &accounts.account_infos(), signer_seeds)
+}
+
+pub fn mint_nft(accounts: &MintNft) -> ProgramResult {
+  invoke(&accounts.instruction(), &accounts.account_infos())
+}
+
+/// mint with a PDA of the calling program as mint authority
+pub fn mint_nft_signed(accounts: &MintNft, signer_seeds: &[&[&[u8]]]) -> ProgramResult {
+  invoke_signed(&accounts.instruction(), &accounts.account_infos(), signer_seeds)
+}
+
+pub fn nft_transaction(accounts: &NftTransaction, data: NftTransactionArgs) -> ProgramResult {
+  invoke(&accounts.instruction(data), &accounts.account_infos())
+}
+
+/// sell with a PDA of the calling program as payer or seller
+pub fn nft_transaction_signed(
+  accounts: &NftTransaction,
+  data: NftTransactionArgs,
+  signer_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+  invoke_signed(&accounts.instruction(data), &accounts.account_infos(), signer_seeds)
+}
@@ -1,37 +1,30 @@
 use crate::{
-  error::ReeMetaError,
+  artNft,
+  instruction::CreateMetadataArgs,
+  kind::NftKind,
   state::{
-    Metadata,
     CustomNft,
+    Kind,
   },
 };
-use borsh::BorshSerialize;
 
 use solana_program::{
   account_info::AccountInfo,
   entrypoint::ProgramResult,
+  pubkey::Pubkey,
 };
 
-pub fn lock_nft(
-  mut metadata: Metadata<CustomNft>,
-  metadata_account_info: &AccountInfo,
-  update_authority_account_info: &AccountInfo,
-) -> ProgramResult {
-  if !metadata.is_modifiable {
-    return Err(ReeMetaError::AlreadyLocked.into())
-  }
-
-  if metadata.update_authority == Option::None {
-    return Err(ReeMetaError::NoUpdateAuthority.into())
-  }
+// custom data only supports the shared lock and sale, every other instruction refuses it
+impl NftKind for CustomNft {
+  const KIND: Kind = Kind::Uninitialized;
+  const NAME: &'static str = "Custom";
 
-  if !update_authority_account_info.is_signer || metadata.update_authority != Some(*update_authority_account_info.key) {
-    return Err(ReeMetaError::InvalidUpdateAuthority.into())
+  // the first release wrote ArtNFT data for any kind, kept for clients creating kind 0
+  fn create(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: CreateMetadataArgs,
+  ) -> ProgramResult {
+    artNft::create(program_id, accounts, data)
   }
-
-  // validated data lock the NFT
-  metadata.is_modifiable = false;
-
-  metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
-  Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,263 @@
+use crate::{
+  state::{ArtNft, GameItem, Kind, Metadata, Royalty, StatValue, UpdateType},
+  utils::json_string,
+};
+
+use solana_program::pubkey::Pubkey;
+use thiserror::Error;
+
+/// why raw account data could not be decoded
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum DecodeError {
+  #[error("account data is empty")]
+  Empty,
+  #[error("unknown metadata kind {0}")]
+  UnknownKind(u8),
+  #[error("account data is truncated or invalid for {kind}: {reason}")]
+  Malformed { kind: &'static str, reason: String },
+}
+
+/// metadata decoded from raw account data, one variant per Kind
+#[derive(PartialEq, Debug, Clone)]
+pub enum DecodedMetadata {
+  RoyaltyArt(Metadata<ArtNft>),
+  GameItem(Metadata<GameItem>),
+}
+
+impl DecodedMetadata {
+  pub fn to_json(&self) -> String {
+    match self {
+      DecodedMetadata::RoyaltyArt(metadata) => art_nft_to_json(metadata),
+      DecodedMetadata::GameItem(metadata) => game_item_to_json(metadata),
+    }
+  }
+}
+
+/// decode metadata account data, dispatching on the kind byte like Metadata::get_kind
+pub fn decode_metadata(data: &[u8]) -> Result<DecodedMetadata, DecodeError> {
+  let kind_u8 = *data.first().ok_or(DecodeError::Empty)?;
+
+  match Kind::from(&kind_u8) {
+    Kind::RoyaltyArt => {
+      let metadata = Metadata::<ArtNft>::from_slice(data)
+        .map_err(|err| DecodeError::Malformed { kind: "RoyaltyArt", reason: err.to_string() })?;
+      Ok(DecodedMetadata::RoyaltyArt(metadata))
+    },
+    Kind::GameItem => {
+      let metadata = Metadata::<GameItem>::from_slice(data)
+        .map_err(|err| DecodeError::Malformed { kind: "GameItem", reason: err.to_string() })?;
+      Ok(DecodedMetadata::GameItem(metadata))
+    },
+    Kind::Uninitialized => Err(DecodeError::UnknownKind(kind_u8)),
+  }
+}
+
+/// decode metadata account data straight to JSON
+pub fn decode_metadata_json(data: &[u8]) -> Result<String, DecodeError> {
+  Ok(decode_metadata(data)?.to_json())
+}
+
+fn pubkey_json(key: &Pubkey) -> String {
+  json_string(&key.to_string())
+}
+
+fn option_pubkey_json(key: &Option<Pubkey>) -> String {
+  match key {
+    None => "null".to_string(),
+    Some(key) => pubkey_json(key),
+  }
+}
+
+fn royalties_json(royalties: &Vec<Royalty>) -> String {
+  let royalties: Vec<String> = royalties.iter().map(|royalty| format!(
+    "{{\"address\":{},\"share\":{},\"verified\":{}}}",
+    pubkey_json(&royalty.address),
+    royalty.share,
+    royalty.verified,
+  )).collect();
+  format!("[{}]", royalties.join(","))
+}
+
+fn update_type_json(update_type: &UpdateType) -> String {
+  let name = match update_type {
+    UpdateType::None => "None",
+    UpdateType::WalletSigner => "WalletSigner",
+    UpdateType::NftToken => "NftToken",
+    UpdateType::Multisig => "Multisig",
+  };
+  json_string(name)
+}
+
+/// the decoded ArtNFT metadata with base58 keys
+pub fn art_nft_to_json(metadata: &Metadata<ArtNft>) -> String {
+  let attributes: Vec<String> = metadata.data.attributes.iter().map(|(key, value)| format!(
+    "{{\"key\":{},\"value\":{}}}",
+    json_string(key),
+    json_string(value),
+  )).collect();
+
+  format!(
+    "{{\"kind\":\"RoyaltyArt\",\"mint\":{},\"is_modifiable\":{},\"update_type\":{},\"collection\":{},\"update_authority\":{},\"data\":{{\"name\":{},\"symbol\":{},\"uri\":{},\"resale_fee\":{},\"initial_sale\":{},\"collection\":{},\"royalties\":{},\"royalty_vault\":{},\"primary_split\":{},\"enforced\":{},\"attributes\":[{}],\"soulbound\":{}}}}}",
+    pubkey_json(&metadata.mint),
+    metadata.is_modifiable,
+    update_type_json(&metadata.update_type),
+    option_pubkey_json(&metadata.collection),
+    option_pubkey_json(&metadata.update_authority),
+    json_string(&metadata.data.name),
+    json_string(&metadata.data.symbol),
+    json_string(&metadata.data.uri),
+    metadata.data.resale_fee,
+    metadata.data.initial_sale,
+    option_pubkey_json(&metadata.data.collection),
+    royalties_json(&metadata.data.royalties),
+    metadata.data.royalty_vault,
+    royalties_json(&metadata.data.primary_split),
+    metadata.data.enforced,
+    attributes.join(","),
+    metadata.data.soulbound,
+  )
+}
+
+fn stat_value_json(value: &StatValue) -> String {
+  match value {
+    StatValue::Int(value) => value.to_string(),
+    StatValue::Bool(value) => value.to_string(),
+    StatValue::Text(value) => json_string(value),
+  }
+}
+
+/// the decoded GameItem metadata with base58 keys
+pub fn game_item_to_json(metadata: &Metadata<GameItem>) -> String {
+  let stats: Vec<String> = metadata.data.stats.iter().map(|(key, value)| format!(
+    "{{\"key\":{},\"value\":{}}}",
+    json_string(key),
+    stat_value_json(value),
+  )).collect();
+
+  format!(
+    "{{\"kind\":\"GameItem\",\"mint\":{},\"is_modifiable\":{},\"update_type\":{},\"collection\":{},\"update_authority\":{},\"data\":{{\"name\":{},\"symbol\":{},\"uri\":{},\"game_authority\":{},\"stats\":[{}]}}}}",
+    pubkey_json(&metadata.mint),
+    metadata.is_modifiable,
+    update_type_json(&metadata.update_type),
+    option_pubkey_json(&metadata.collection),
+    option_pubkey_json(&metadata.update_authority),
+    json_string(&metadata.data.name),
+    json_string(&metadata.data.symbol),
+    json_string(&metadata.data.uri),
+    pubkey_json(&metadata.data.game_authority),
+    stats.join(","),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use borsh::BorshSerialize;
+  use assert_matches::assert_matches;
+
+  fn art_nft() -> Metadata<ArtNft> {
+    Metadata{
+      kind: Kind::RoyaltyArt,
+      mint: Pubkey::new_unique(),
+      is_modifiable: true,
+      update_type: UpdateType::WalletSigner,
+      collection: None,
+      update_authority: Some(Pubkey::new_unique()),
+      data: ArtNft{
+        name: "art".to_string(),
+        symbol: "ART".to_string(),
+        uri: "https://example.com/art.json".to_string(),
+        resale_fee: 10,
+        initial_sale: true,
+        collection: None,
+        royalties: vec![Royalty{ address: Pubkey::new_unique(), share: 100, verified: true }],
+        royalty_vault: false,
+        primary_split: Vec::new(),
+        enforced: false,
+        attributes: vec![("color".to_string(), "blue".to_string())],
+        soulbound: false,
+      },
+    }
+  }
+
+  fn game_item() -> Metadata<GameItem> {
+    Metadata{
+      kind: Kind::GameItem,
+      mint: Pubkey::new_unique(),
+      is_modifiable: false,
+      update_type: UpdateType::None,
+      collection: None,
+      update_authority: None,
+      data: GameItem{
+        name: "sword".to_string(),
+        symbol: "SWD".to_string(),
+        uri: "https://example.com/sword.json".to_string(),
+        game_authority: Pubkey::new_unique(),
+        stats: vec![
+          ("level".to_string(), StatValue::Int(3)),
+          ("cursed".to_string(), StatValue::Bool(false)),
+          ("owner".to_string(), StatValue::Text("\"ree\"".to_string())),
+        ],
+      },
+    }
+  }
+
+  #[test]
+  fn test_empty() {
+    assert_eq!(decode_metadata(&[]), Err(DecodeError::Empty));
+  }
+
+  #[test]
+  fn test_unknown_kind() {
+    let mut data = art_nft().try_to_vec().unwrap();
+    for kind in [0, 3, 255] {
+      data[0] = kind;
+      assert_eq!(decode_metadata(&data), Err(DecodeError::UnknownKind(kind)));
+    }
+  }
+
+  #[test]
+  fn test_truncated_royalty_art() {
+    let data = art_nft().try_to_vec().unwrap();
+    assert_matches!(
+      decode_metadata(&data[..40]),
+      Err(DecodeError::Malformed { kind: "RoyaltyArt", .. })
+    );
+  }
+
+  #[test]
+  fn test_truncated_game_item() {
+    let data = game_item().try_to_vec().unwrap();
+    assert_matches!(
+      decode_metadata(&data[..data.len() - 1]),
+      Err(DecodeError::Malformed { kind: "GameItem", .. })
+    );
+  }
+
+  #[test]
+  fn test_royalty_art_json() {
+    let metadata = art_nft();
+    let decoded = decode_metadata(&metadata.try_to_vec().unwrap()).unwrap();
+    assert_eq!(decoded, DecodedMetadata::RoyaltyArt(metadata.clone()));
+
+    assert_eq!(decoded.to_json(), format!(
+      "{{\"kind\":\"RoyaltyArt\",\"mint\":\"{}\",\"is_modifiable\":true,\"update_type\":\"WalletSigner\",\"collection\":null,\"update_authority\":\"{}\",\"data\":{{\"name\":\"art\",\"symbol\":\"ART\",\"uri\":\"https://example.com/art.json\",\"resale_fee\":10,\"initial_sale\":true,\"collection\":null,\"royalties\":[{{\"address\":\"{}\",\"share\":100,\"verified\":true}}],\"royalty_vault\":false,\"primary_split\":[],\"enforced\":false,\"attributes\":[{{\"key\":\"color\",\"value\":\"blue\"}}],\"soulbound\":false}}}}",
+      metadata.mint,
+      metadata.update_authority.unwrap(),
+      metadata.data.royalties[0].address,
+    ));
+  }
+
+  #[test]
+  fn test_game_item_json() {
+    let metadata = game_item();
+    let decoded = decode_metadata(&metadata.try_to_vec().unwrap()).unwrap();
+    assert_eq!(decoded, DecodedMetadata::GameItem(metadata.clone()));
+
+    assert_eq!(decoded.to_json(), format!(
+      "{{\"kind\":\"GameItem\",\"mint\":\"{}\",\"is_modifiable\":false,\"update_type\":\"None\",\"collection\":null,\"update_authority\":null,\"data\":{{\"name\":\"sword\",\"symbol\":\"SWD\",\"uri\":\"https://example.com/sword.json\",\"game_authority\":\"{}\",\"stats\":[{{\"key\":\"level\",\"value\":3}},{{\"key\":\"cursed\",\"value\":false}},{{\"key\":\"owner\",\"value\":\"\\\"ree\\\"\"}}]}}}}",
+      metadata.mint,
+      metadata.data.game_authority,
+    ));
+  }
+}
@@ -31,6 +31,84 @@ pub enum ReeMetaError {
   InsufficientShare,
   #[error("Invalid Royalty Address")]
   RoyaltyAddressInvalid,
+  #[error("Invalid Royalty Vault")]
+  InvalidVault,
+  #[error("Royalty Vault Already Enabled")]
+  VaultAlreadyEnabled,
+  #[error("Nothing To Claim")]
+  NothingToClaim,
+  #[error("Invalid Resale Fee")]
+  InvalidResaleFee,
+  #[error("Royalty Shares Must Total 100")]
+  InvalidShareTotal,
+  #[error("Duplicate Royalty Address")]
+  DuplicateRoyaltyAddress,
+  #[error("Too Many Royalties")]
+  TooManyRoyalties,
+  #[error("Royalty Share Must Be Above Zero")]
+  ZeroShare,
+  #[error("Initial Sale Already Completed")]
+  InitialSaleCompleted,
+  #[error("Invalid Freeze Authority")]
+  InvalidFreezeAuthority,
+  #[error("Invalid Token Account")]
+  InvalidTokenAccount,
+  #[error("Royalties Not Enforced")]
+  NotEnforced,
+  #[error("Invalid Metaplex Metadata")]
+  InvalidMetaplexMetadata,
+  #[error("Too Many Attributes")]
+  TooManyAttributes,
+  #[error("Invalid Attribute")]
+  InvalidAttribute,
+  #[error("Attribute Not Found")]
+  AttributeNotFound,
+  #[error("Invalid Multisig")]
+  InvalidMultisig,
+  #[error("Multisig Threshold Not Met")]
+  MultisigThresholdNotMet,
+  #[error("Invalid Delegate")]
+  InvalidDelegate,
+  #[error("Delegate Scope Not Granted")]
+  DelegateScopeNotGranted,
+  #[error("Delegate Expired")]
+  DelegateExpired,
+  #[error("Invalid Metadata Account")]
+  InvalidMetadataAccount,
+  #[error("Missing Required Signer")]
+  MissingSigner,
+  #[error("Account Not Writable")]
+  AccountNotWritable,
+  #[error("Invalid Account Key")]
+  InvalidAccountKey,
+  #[error("Invalid Sale Fee")]
+  InvalidSaleFee,
+  #[error("Invalid Program Config")]
+  InvalidConfig,
+  #[error("Program Paused")]
+  ProgramPaused,
+  #[error("Invalid Stat")]
+  InvalidStat,
+  #[error("Invalid Game Authority")]
+  InvalidGameAuthority,
+  #[error("Soulbound NFT")]
+  SoulboundNft,
+  #[error("No Permanent Delegate")]
+  NoPermanentDelegate,
+  #[error("Invalid Distributor")]
+  InvalidDistributor,
+  #[error("Invalid Fraction Mint")]
+  InvalidFractionMint,
+  #[error("Insufficient Stake")]
+  InsufficientStake,
+  #[error("Math Overflow")]
+  MathOverflow,
+  #[error("Invalid Sale Amount")]
+  InvalidSaleAmount,
+  #[error("Invalid Buyer")]
+  InvalidBuyer,
+  #[error("Name, Symbol Or Uri Too Long")]
+  StringTooLong,
 }
 
 impl PrintProgramError for ReeMetaError {
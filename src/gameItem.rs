@@ -0,0 +1,200 @@
+use crate::{
+  error::ReeMetaError,
+  instruction::{CreateMetadataArgs, StatChange, UpdateStatsArgs},
+  kind::{CreateAccounts, NftKind},
+  state::{
+    Metadata,
+    GameItem,
+    Kind,
+  },
+  utils::resize_account,
+};
+use borsh::BorshSerialize;
+
+use solana_program::{
+  account_info::{AccountInfo, next_account_info},
+  entrypoint::ProgramResult,
+  msg,
+  pubkey::Pubkey,
+  system_program,
+  sysvar::{rent::{Rent, ID as RENT_ID}, Sysvar},
+};
+
+/// create the metadata of a GameItem. the game authority is the only account that can
+/// write its stats, it can be a PDA of the game program signing through invoke_signed
+pub fn create(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: CreateMetadataArgs,
+) -> ProgramResult {
+  let create_accounts = CreateAccounts::from_accounts(program_id, accounts, &data.metadata)?;
+
+  if data.aar_data.resale_fee != 0 || data.aar_data.enforced || data.aar_data.soulbound {
+    msg!("game items have no resale fee and can't be enforced or soulbound");
+    return Err(ReeMetaError::InvalidInstruction.into())
+  }
+
+  let game_item = GameItem{
+    name: data.aar_data.name,
+    symbol: data.aar_data.symbol,
+    uri: data.aar_data.uri,
+    game_authority: *create_accounts.owner.key,
+    stats: Vec::new(),
+  };
+  game_item.validate()?;
+
+  let metadata = create_accounts.new_metadata(&data.metadata, game_item);
+
+  msg!("game authority {}", create_accounts.owner.key.to_string());
+  create_accounts.create_metadata(program_id, &metadata)
+}
+
+/// apply the changes in order, a value sets or adds the stat and none removes it
+fn apply_stat_changes(game_item: &mut GameItem, changes: Vec<StatChange>) -> ProgramResult {
+  for change in changes.into_iter() {
+    let position = game_item.stats.iter().position(|(key, _)| *key == change.key);
+    match (position, change.value) {
+      (Some(i), Some(value)) => game_item.stats[i].1 = value,
+      (None, Some(value)) => game_item.stats.push((change.key, value)),
+      (Some(i), None) => {
+        game_item.stats.remove(i);
+      },
+      (None, None) => {
+        msg!("stat {} not found", change.key);
+        return Err(ReeMetaError::InvalidStat.into())
+      },
+    }
+  }
+
+  Ok(())
+}
+
+/// write stat changes from the game authority. a PDA of the game program signs through
+/// invoke_signed. the stats are not metadata fields so a locked item still takes changes
+pub fn update_stats(
+  _program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  mut metadata: Metadata<GameItem>,
+  data: UpdateStatsArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+  let payer_account_info = next_account_info(account_iter)?;
+  let game_authority_account_info = next_account_info(account_iter)?;
+  let system_info = next_account_info(account_iter)?;
+  let rent_info = next_account_info(account_iter)?;
+
+  if *system_info.key != system_program::ID || *rent_info.key != RENT_ID {
+    return Err(ReeMetaError::InvalidInstruction.into())
+  }
+
+  if !game_authority_account_info.is_signer
+    || *game_authority_account_info.key != metadata.data.game_authority
+  {
+    msg!("game authority must sign");
+    return Err(ReeMetaError::InvalidGameAuthority.into())
+  }
+
+  apply_stat_changes(&mut metadata.data, data.changes)?;
+  metadata.data.validate()?;
+
+  let rent = &Rent::from_account_info(rent_info)?;
+  resize_account(
+    metadata_account_info,
+    payer_account_info,
+    system_info,
+    rent,
+    metadata.size(),
+  )?;
+
+  metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
+  Ok(())
+}
+
+impl NftKind for GameItem {
+  const KIND: Kind = Kind::GameItem;
+  const NAME: &'static str = "GameItem";
+
+  fn create(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: CreateMetadataArgs,
+  ) -> ProgramResult {
+    create(program_id, accounts, data)
+  }
+
+  fn update_stats(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata: Metadata<Self>,
+    data: UpdateStatsArgs,
+  ) -> ProgramResult {
+    update_stats(program_id, accounts, metadata, data)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::state::StatValue;
+  use assert_matches::assert_matches;
+
+  fn game_item(stats: Vec<(&str, StatValue)>) -> GameItem {
+    GameItem{
+      name: "sword".to_string(),
+      symbol: "SWD".to_string(),
+      uri: "https://example.com/sword.json".to_string(),
+      game_authority: Pubkey::new_unique(),
+      stats: stats.into_iter().map(|(key, value)| (key.to_string(), value)).collect(),
+    }
+  }
+
+  fn set(key: &str, value: StatValue) -> StatChange {
+    StatChange{ key: key.to_string(), value: Some(value) }
+  }
+
+  fn remove(key: &str) -> StatChange {
+    StatChange{ key: key.to_string(), value: None }
+  }
+
+  #[test]
+  fn test_set_add_and_remove() {
+    let mut item = game_item(vec![("level", StatValue::Int(1)), ("cursed", StatValue::Bool(true))]);
+    let changes = vec![
+      set("level", StatValue::Int(2)),
+      remove("cursed"),
+      set("owner", StatValue::Text("guild".to_string())),
+    ];
+
+    assert_matches!(apply_stat_changes(&mut item, changes), Ok(()));
+    assert_eq!(item.stats, game_item(vec![
+      ("level", StatValue::Int(2)),
+      ("owner", StatValue::Text("guild".to_string())),
+    ]).stats);
+  }
+
+  #[test]
+  fn test_changes_apply_in_order() {
+    let mut item = game_item(vec![("level", StatValue::Int(1))]);
+    let changes = vec![
+      remove("level"),
+      set("level", StatValue::Int(5)),
+      set("level", StatValue::Int(6)),
+      set("xp", StatValue::Int(10)),
+      remove("xp"),
+    ];
+
+    assert_matches!(apply_stat_changes(&mut item, changes), Ok(()));
+    assert_eq!(item.stats, vec![("level".to_string(), StatValue::Int(6))]);
+  }
+
+  #[test]
+  fn test_remove_missing_stat() {
+    let mut item = game_item(vec![("level", StatValue::Int(1))]);
+    // the stat is already gone when the second removal runs
+    assert_eq!(
+      apply_stat_changes(&mut item, vec![remove("level"), remove("level")]),
+      Err(ReeMetaError::InvalidStat.into())
+    );
+  }
+}
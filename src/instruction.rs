@@ -8,12 +8,14 @@ use borsh::{BorshSerialize};
 
 use crate::{
   error::ReeMetaError::InvalidInstruction,
-  state::{Kind, UpdateType},
+  pda::{find_config_address, find_distributor_address, find_fraction_stake_address},
+  state::{Kind, StatValue, UpdateType},
   unpack::unpack_string,
 };
 
 #[repr(C)]
 #[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MetadataArgs {
   pub kind: Kind,
   pub is_modifiable: bool,
@@ -24,16 +26,19 @@ pub struct MetadataArgs {
 // TODO: update to MetadataArgsRRA ResaleRoyaltyArt
 #[repr(C)]
 #[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MetadataArgsRRA {
   pub name: String,
   pub symbol: String,
   pub uri: String,
   pub resale_fee: u16,
-  
+  pub enforced: bool,
+  pub soulbound: bool,
 }
 
 #[repr(C)]
 #[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CreateMetadataArgs {
   pub metadata: MetadataArgs,
   pub aar_data: MetadataArgsRRA,
@@ -41,76 +46,452 @@ pub struct CreateMetadataArgs {
 
 #[repr(C)]
 #[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AddRoyaltyArgs {
   pub share: u16,
 }
 
 #[repr(C)]
 #[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NftTransactionArgs {
   pub amount: u64,
+  pub marketplace_fee: Option<SaleFee>,
+  pub referral_fee: Option<SaleFee>,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SaleFee {
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+  pub recipient: Pubkey,
+  pub basis_points: u16,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SetPrimarySplitArgs {
+  pub shares: Vec<u16>,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SetAttributeArgs {
+  pub key: String,
+  pub value: String,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RemoveAttributeArgs {
+  pub key: String,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InitMultisigArgs {
+  pub threshold: u8,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ApproveDelegateArgs {
+  pub scope: u8,
+  pub expiry: i64,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UpdateUriArgs {
+  pub uri: String,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConfigArgs {
+  pub protocol_fee: u16,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SetPausedArgs {
+  pub paused: bool,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuoteRoyaltyArgs {
+  pub amount: u64,
 }
 
+/// set a stat to value, a stat without a value is removed
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatChange {
+  pub key: String,
+  pub value: Option<StatValue>,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UpdateStatsArgs {
+  pub changes: Vec<StatChange>,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FractionalizeArgs {
+  pub supply: u64,
+}
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, BorshSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FractionAmountArgs {
+  pub amount: u64,
+}
+
+/// breaking in 2.0.0: CreateMetaData, NftTransaction and ImportFromMetaplex fail unless the
+/// program config PDA is among their accounts, so the pause can't be skipped by leaving it
+/// out. the builders here add it, 1.x clients building these instructions by hand have to.
 #[repr(C)]
 #[derive(PartialEq, Debug, Clone, BorshSerialize)]
 pub enum ReeMetadataInstruction {
-  /* Create ArtNFT Metadata account
-   * creates the metadata account data giving ownership to program and setting details
+  /* Create Metadata account
+   * creates the metadata account data giving ownership to program and setting details.
+   * the kind picks the data: RoyaltyArt creates an ArtNFT, GameItem a game item whose stats
+   * only the game authority can write. game items take no resale fee, enforced or soulbound.
    * #[account(0), writable, name="metadata_account", desc="PDA of the new metadata account"]
    * #[account(1), writable, name="mint", desc="Mint of the token asset"]
-   * #[account(2), read, name="royalty_owner", desc="Original royalty holder that starts with 100% of the shares"]
+   * #[account(2), read, name="royalty_owner", desc="Original royalty holder that starts with 100% of the shares, the game authority of a GameItem"]
    * #[account(3), signer, name="created_mint_authority", desc="Mint authority of the mint"]
-   * #[account(4), read, name="nft_mint_authority", desc="Pubkey of who created the mint"]
-   * #[account(5), read, name="new_nft_mint_authority", desc="pubkey of who can mint the 1 nft"]
-   * #[account(6), writable & signer, name="payer", desc="Payer of the transaction"]
-   * #[account(7), optional, name="update_authority", desc="if the metadata is mutable then this needs to be either the wallet of the updater or an NFT wallet" ]
-   * #[account(8), name="system_program", desc="System Program"]
-   * #[account(9), name="rent", "Rent info"]
-   * #[account(10), name="token_program", desc="Token Program"]
-   * #[account(11), read & optional, name="collection", description="collection key if part of collection"]
+   * #[account(4), read, name="new_nft_mint_authority", desc="pubkey of who can mint the 1 nft"]
+   * #[account(5), writable & signer, name="payer", desc="Payer of the transaction"]
+   * #[account(6), optional, name="update_authority", desc="if the metadata is mutable then this needs to be either the wallet of the updater, an NFT wallet or a multisig config" ]
+   * #[account(7), name="system_program", desc="System Program"]
+   * #[account(8), name="rent", "Rent info"]
+   * #[account(9), name="token_program", desc="spl_token or Token-2022 program owning the mint"]
+   * #[account(10), read & optional, name="collection", description="collection key if part of collection"]
+   * #[account(any), read, name="config", desc="program config PDA, required since 2.0.0 even before InitConfig"]
+   * when enforced is set the mint authority must also be the freeze authority of the mint, which is
+   * handed to the freeze authority PDA so holder token accounts stay frozen between ree-meta transfers.
+   * soulbound hands it over the same way, the token is frozen for good and can't be sold or transferred.
+   * soulbound needs a Token-2022 mint with the freeze authority PDA as permanent delegate, Revoke burns with it
    */
   CreateMetaData(CreateMetadataArgs),
   /* Mint one token of the given NFT and close the mint
    * #[account(0), writable, name="mint", desc="Mint of the NFT"]
    * #[account(1), signer, name="Mint_authority", desc="Mint authority and payer"] 
    * #[account(2), writable, name=recipient_ta", desc="Recipient token account"]
-   * #[account(4), name="token_program", desc="token program"]
+   * #[account(3), name="token_program", desc="spl_token or Token-2022 program owning the mint"]
+   * #[account(4), optional, name="freeze_authority", desc="freeze authority PDA, required when royalties are enforced or soulbound"]
    */
   MintNFT(),
   /* Lock metadata
    * lock the metadata from any further changes other then Initial Sale
    * #[account(0), writable, name='metadata', desc="PDA of the NFT metadata"]
-   * #[account(1), signer & writable, name="payer", desc="transaction payer"]
+   * #[account(1), signer, name="payer", desc="transaction payer"]
    * #[account(2), signer, name="update_authority", desc-"update authority of the NFT"]
    */
   LockNFT(),
   /* Add Royalty to ArtNFT
    * can add a Royalty to the ArtNFT Royalty list. Will take Share from the 
-   * Royalty in position 0, NFT must me modifiable.
+   * Royalty in position 0, NFT must me modifiable. a fractionalized share can't be taken from.
    * #[account(0), writable, name="metadata", desc="PDA of the NFT metadata"]
    * #[account(1), signer & writable, name="payer", desc="Transaction & Rent Payer" ]
    * #[account(2), signer, name="update_authority", desc="update authority of the NFT"]
    * #[account(3), read, name="new_royalty", desc="pubkey of royalty to add"]
    * #[account(4), read, name="system_program"]
    * #[account(5), read, name="rent_program"]
+   * #[account(any), read, name="first_royalty", desc="account of the royalty in position 0"]
    */
   AddRoyalty(AddRoyaltyArgs),
-  /* Perform an NFT payout
-   * Process a Nft payment transfer.
+  /* Perform an NFT sale
+   * Move the NFT from the seller to the buyer and pay the amount out in one instruction,
+   * going through the freeze authority PDA when royalties are enforced.
    * If the kind of nft contains a royalty system apply the royalty system
    * -> if initial sale is false then ignore the target and 
    *      apply the full transfer to the royalty system. change the initial sale to true.
+   *      when a primary split is set it is paid instead of the royalties.
    * -> if initial sale is true the take the resale_fee out of the transfer
    *       give the remainder to the target address
    *       the resale fee goes through the royalty system
-   * If no royalty system exists on the nft simply transfer the funds to the target.
+   * The royalty system splits by share with the largest remainder method. A payout that
+   * would leave its recipient below rent exemption is credited to them in the vault instead.
+   * The initial sale only completes when the token of the metadata mint is transferred and
+   * the amount is not zero. Kinds without a royalty system pay the full amount to the target.
+   * The buyer token account must belong to the payer and the payer can't be the target.
+   * The protocol fee of the program config and the optional marketplace and referral fees
+   * are basis points of the amount, capped at 5%, 10% and 5%. On a resale they come out of
+   * what the target receives, never the royalties. The initial sale pays everything to the
+   * royalties or the primary split, so it rejects marketplace and referral fees and takes the
+   * protocol fee from the amount before the split.
    * #[account(0), writable, name="metadata", desc="PDA of the NFT metadata"]
    * #[account(1), signer & writable, name="payer", desc="Transaction payer and NFT buyer"]
-   * #[account(2), writable, name="target", "seller of the NFT and possble recipient of funds"]
-   * #[acconut(3), read, name="system_program"]
-   * #[account(4-x), optional & writable, name="royalty accounts", "Inclued if needed"]
+   * #[account(2), signer & writable, name="target", desc="seller of the NFT and recipient of funds"]
+   * #[account(3), read, name="system_program"]
+   * #[account(4), read, name="mint", desc="Mint of the NFT"]
+   * #[account(5), read, name="freeze_authority", desc="freeze authority PDA of the mint"]
+   * #[account(6), writable, name="seller_token_account", desc="token account holding the NFT"]
+   * #[account(7), writable, name="buyer_token_account", desc="token account receiving the NFT"]
+   * #[account(8), read, name="token_program"]
+   * #[account(9), optional & writable, name="marketplace", desc="marketplace fee recipient, when a marketplace fee is set"]
+   * #[account(10), optional & writable, name="referrer", desc="referral fee recipient, when a referral fee is set"]
+   * #[account(11-x), optional & writable, name="royalty accounts", "Inclued if needed, primary split accounts on the initial sale, not used when the vault is enabled"]
+   * #[account(x+1), optional & writable, name="vault", desc="royalty vault PDA, needed when the vault is enabled or a payout is carried forward"]
+   * #[account(any), read, name="config", desc="program config PDA, required since 2.0.0 even before InitConfig"]
+   * #[account(any), optional & writable, name="protocol_fee_recipient", desc="fee recipient of the config, when a protocol fee is set"]
    */
   NftTransaction(NftTransactionArgs),
+  /* Create Royalty Vault
+   * creates the royalty vault PDA for an ArtNFT and routes every following royalty
+   * payout into it instead of paying each royalty account. NFT must be modifiable.
+   * #[account(0), writable, name="metadata", desc="PDA of the NFT metadata"]
+   * #[account(1), signer & writable, name="payer", desc="Transaction & Rent Payer"]
+   * #[account(2), signer, name="update_authority", desc="update authority of the NFT"]
+   * #[account(3), writable, name="vault", desc="PDA of the royalty vault"]
+   * #[account(4), read, name="system_program"]
+   * #[account(5), read, name="rent_program"]
+   */
+  CreateRoyaltyVault(),
+  /* Claim Royalties
+   * withdraw the lamports accrued in the royalty vault for the signing royalty holder.
+   * the vault only holds lamports as NftTransaction only takes SOL.
+   * #[account(0), writable, name="vault", desc="PDA of the royalty vault"]
+   * #[account(1), signer & writable, name="holder", desc="royalty holder receiving the lamports"]
+   */
+  ClaimRoyalties(),
+  /* Set Primary Split
+   * set the split used for the initial sale of an ArtNFT instead of the royalties.
+   * one share per split account in the same order, an empty list clears the split.
+   * NFT must be modifiable and the initial sale not done yet.
+   * #[account(0), writable, name="metadata", desc="PDA of the NFT metadata"]
+   * #[account(1), signer & writable, name="payer", desc="Transaction & Rent Payer"]
+   * #[account(2), signer, name="update_authority", desc="update authority of the NFT"]
+   * #[account(3), read, name="system_program"]
+   * #[account(4), read, name="rent_program"]
+   * #[account(5-x), read, name="split accounts", desc="pubkey of each primary sale recipient"]
+   */
+  SetPrimarySplit(SetPrimarySplitArgs),
+  /* Transfer an enforced NFT
+   * thaw the token accounts with the freeze authority PDA, move the NFT and freeze them again.
+   * No payment is made so no royalties are paid. Holders can't call it alone, or a sale agreed
+   * elsewhere could skip the royalties: the owner and the update authority both sign, e.g. to
+   * move the NFT to another wallet of the owner. Holders sell with Buy or NftTransaction, which
+   * thaw the same way and collect the royalties by the ArtNft rules.
+   * #[account(0), read, name="metadata", desc="PDA of the NFT metadata"]
+   * #[account(1), signer, name="update_authority", desc="update authority of the NFT"]
+   * #[account(2), read, name="mint", desc="Mint of the NFT"]
+   * #[account(3), read, name="freeze_authority", desc="freeze authority PDA of the mint"]
+   * #[account(4), signer, name="owner", desc="owner of the source token account"]
+   * #[account(5), writable, name="source", desc="token account holding the NFT"]
+   * #[account(6), writable, name="destination", desc="token account receiving the NFT"]
+   * #[account(7), read, name="token_program"]
+   */
+  Transfer(),
+  /* Buy an NFT
+   * the sale of NftTransaction under its own tag, with the same accounts, checks and payouts.
+   * an enforced NFT only changes hands through Buy, NftTransaction or an approved Transfer.
+   */
+  Buy(NftTransactionArgs),
+  /* Import from Metaplex
+   * create the ArtNFT metadata for a mint from its Metaplex Token Metadata account.
+   * name, symbol and uri are copied, seller_fee_basis_points becomes the resale fee
+   * rounded down to a whole percent and the creators become the royalties.
+   * #[account(0), writable, name="metadata", desc="PDA of the new metadata account"]
+   * #[account(1), read, name="mint", desc="Mint of the NFT"]
+   * #[account(2), read, name="metaplex_metadata", desc="Metaplex metadata PDA of the mint"]
+   * #[account(3), signer & writable, name="payer", desc="Transaction & Rent Payer"]
+   * #[account(4), signer, name="update_authority", desc="update authority of the Metaplex metadata"]
+   * #[account(5), read, name="system_program"]
+   * #[account(6), read, name="rent_program"]
+   * #[account(any), read, name="config", desc="program config PDA, required since 2.0.0 even before InitConfig"]
+   */
+  ImportFromMetaplex(),
+  /* Set Attribute
+   * add a trait attribute to the ArtNFT or replace the value of an existing key.
+   * NFT must be modifiable.
+   * #[account(0), writable, name="metadata", desc="PDA of the NFT metadata"]
+   * #[account(1), signer & writable, name="payer", desc="Transaction & Rent Payer"]
+   * #[account(2), signer, name="update_authority", desc="update authority of the NFT"]
+   * #[account(3), read, name="system_program"]
+   * #[account(4), read, name="rent_program"]
+   */
+  SetAttribute(SetAttributeArgs),
+  /* Remove Attribute
+   * remove a trait attribute from the ArtNFT. NFT must be modifiable.
+   * #[account(0), writable, name="metadata", desc="PDA of the NFT metadata"]
+   * #[account(1), signer & writable, name="payer", desc="transaction payer"]
+   * #[account(2), signer, name="update_authority", desc="update authority of the NFT"]
+   * #[account(3), read, name="system_program"]
+   * #[account(4), read, name="rent_program"]
+   */
+  RemoveAttribute(RemoveAttributeArgs),
+  /* Init Multisig
+   * create a multisig config PDA to use as an m of n update authority.
+   * metadata with the Multisig update type uses the config as update_authority and
+   * authority gated instructions need threshold signers anywhere in their accounts.
+   * #[account(0), writable, name="multisig", desc="PDA of the multisig config"]
+   * #[account(1), signer & writable, name="payer", desc="Transaction & Rent Payer"]
+   * #[account(2), signer, name="base", desc="key the multisig PDA is derived from"]
+   * #[account(3), read, name="system_program"]
+   * #[account(4), read, name="rent_program"]
+   * #[account(5-x), read, name="signers", desc="members of the multisig"]
+   */
+  InitMultisig(InitMultisigArgs),
+  /* Approve Delegate
+   * create or replace a delegate record granting scoped update rights until expiry.
+   * scope is a bitmask of uri (1), attributes (2), royalties (4) and lock (8).
+   * expiry is a unix timestamp and has to be in the future.
+   * #[account(0), read, name="metadata", desc="PDA of the NFT metadata"]
+   * #[account(1), signer & writable, name="payer", desc="Transaction & Rent Payer"]
+   * #[account(2), signer, name="update_authority", desc="update authority of the NFT"]
+   * #[account(3), read, name="delegate", desc="key receiving the delegated rights"]
+   * #[account(4), writable, name="delegate_record", desc="PDA of the delegate record"]
+   * #[account(5), read, name="system_program"]
+   * #[account(6), read, name="rent_program"]
+   */
+  ApproveDelegate(ApproveDelegateArgs),
+  /* Revoke Delegate
+   * close a delegate record, signed by the update authority or the delegate itself.
+   * #[account(0), read, name="metadata", desc="PDA of the NFT metadata"]
+   * #[account(1), signer, name="authority", desc="update authority or delegate"]
+   * #[account(2), writable, name="delegate_record", desc="PDA of the delegate record"]
+   * #[account(3), writable, name="receiver", desc="update authority of the NFT, or the delegate when there is none, receives the rent of the record"]
+   */
+  RevokeDelegate(),
+  /* Update Uri
+   * replace the uri of the ArtNFT. NFT must be modifiable.
+   * #[account(0), writable, name="metadata", desc="PDA of the NFT metadata"]
+   * #[account(1), signer & writable, name="payer", desc="Transaction & Rent Payer"]
+   * #[account(2), signer, name="update_authority", desc="update authority or uri delegate"]
+   * #[account(3), read, name="system_program"]
+   * #[account(4), read, name="rent_program"]
+   * #[account(5), read, optional, name="delegate_record", desc="record when a delegate signs"]
+   */
+  UpdateUri(UpdateUriArgs),
+  /* Init Config
+   * create the program config PDA. protocol_fee is in basis points, capped at 5%, and is
+   * charged on every sale, see NftTransaction for where it comes from. creation and sale instructions need
+   * the config PDA anywhere in their accounts so a pause can't be skipped.
+   * the admin has to be the upgrade authority of the program.
+   * #[account(0), writable, name="config", desc="PDA of the program config"]
+   * #[account(1), signer & writable, name="payer", desc="Transaction & Rent Payer"]
+   * #[account(2), signer, name="admin", desc="admin of the program config, the upgrade authority of the program"]
+   * #[account(3), read, name="fee_recipient", desc="receives the protocol fee"]
+   * #[account(4), read, name="system_program"]
+   * #[account(5), read, name="rent_program"]
+   * #[account(6), read, name="program_data", desc="ProgramData account of this program"]
+   */
+  InitConfig(ConfigArgs),
+  /* Update Config
+   * change the admin, protocol fee or fee recipient of the program config.
+   * #[account(0), writable, name="config", desc="PDA of the program config"]
+   * #[account(1), signer, name="admin", desc="current admin of the program config"]
+   * #[account(2), read, name="new_admin", desc="admin from now on, the current admin to keep it"]
+   * #[account(3), read, name="fee_recipient", desc="receives the protocol fee"]
+   */
+  UpdateConfig(ConfigArgs),
+  /* Set Paused
+   * pause or resume metadata creation and sales.
+   * #[account(0), writable, name="config", desc="PDA of the program config"]
+   * #[account(1), signer, name="admin", desc="admin of the program config"]
+   */
+  SetPaused(SetPausedArgs),
+  /* Quote Royalty
+   * read only. quote the royalties due on a sale of amount lamports without marketplace or
+   * referral fees and return a borsh encoded quote::RoyaltyQuote, each royalty address with
+   * its lamports and the seller remainder, through set_return_data for programs calling in
+   * by CPI. the seller remainder is net of the protocol fee when the config is passed.
+   * #[account(0), read, name="metadata", desc="PDA of the NFT metadata"]
+   * #[account(1), optional & read, name="config", desc="program config PDA"]
+   */
+  QuoteRoyalty(QuoteRoyaltyArgs),
+  /* Update Stats
+   * set or remove stats of a GameItem in order. allowed after the metadata is locked.
+   * #[account(0), writable, name="metadata", desc="PDA of the NFT metadata"]
+   * #[account(1), signer & writable, name="payer", desc="Transaction & Rent Payer"]
+   * #[account(2), signer, name="game_authority", desc="game authority of the item"]
+   * #[account(3), read, name="system_program"]
+   * #[account(4), read, name="rent_program"]
+   */
+  UpdateStats(UpdateStatsArgs),
+  /* Revoke
+   * burn a soulbound NFT from its holder. signed by the update authority of the metadata.
+   * the mint has to be a Token-2022 mint with the freeze authority PDA as permanent delegate
+   * #[account(0), read, name="metadata", desc="PDA of the NFT metadata"]
+   * #[account(1), signer, name="update_authority", desc="update authority of the metadata"]
+   * #[account(2), writable, name="mint", desc="Mint of the token asset"]
+   * #[account(3), read, name="freeze_authority", desc="freeze authority PDA of the mint"]
+   * #[account(4), writable, name="token_account", desc="holder token account of the NFT"]
+   * #[account(5), read, name="token_program", desc="Token-2022"]
+   */
+  Revoke(),
+  /* Fractionalize
+   * convert the royalty share of the signing holder into supply fractions of a fungible mint.
+   * the distributor PDA replaces the holder in the royalties and the primary split and receives
+   * their payouts. the fraction mint has no supply, no freeze authority and the distributor as
+   * mint authority, which is removed after minting so the supply is fixed.
+   * #[account(0), writable, name="metadata", desc="PDA of the NFT metadata"]
+   * #[account(1), signer & writable, name="payer", desc="Transaction & Rent Payer"]
+   * #[account(2), signer, name="holder", desc="royalty holder converting the share"]
+   * #[account(3), writable, name="distributor", desc="PDA of the new distributor"]
+   * #[account(4), writable, name="fraction_mint", desc="fungible mint of the fractions"]
+   * #[account(5), writable, name="holder_token_account", desc="receives the fractions"]
+   * #[account(6), read, name="system_program"]
+   * #[account(7), read, name="rent_program"]
+   * #[account(8), read, name="token_program", desc="spl_token or Token-2022 program owning the fraction mint"]
+   */
+  Fractionalize(FractionalizeArgs),
+  /* Deposit Fractions
+   * stake fractions in the distributor escrow, each fraction earns 1/supply of the distributor
+   * payouts. what unstaked fractions earned is reserved and the deposit takes its pro rata
+   * share of it. the stake PDA is created on the first deposit.
+   * #[account(0), writable, name="distributor", desc="PDA of the distributor"]
+   * #[account(1), writable, name="stake", desc="PDA of the owner stake"]
+   * #[account(2), signer & writable, name="owner", desc="fraction owner and rent payer"]
+   * #[account(3), read, name="fraction_mint", desc="fungible mint of the fractions"]
+   * #[account(4), writable, name="owner_token_account", desc="fractions taken from"]
+   * #[account(5), writable, name="escrow_token_account", desc="fraction token account owned by the distributor"]
+   * #[account(6), read, name="token_program"]
+   * #[account(7), read, name="system_program"]
+   * #[account(8), read, name="rent_program"]
+   */
+  DepositFractions(FractionAmountArgs),
+  /* Withdraw Fractions
+   * unstake fractions back to the owner, what they earned so far stays claimable and
+   * from now on they earn into the reserve.
+   * #[account(0), writable, name="distributor", desc="PDA of the distributor"]
+   * #[account(1), writable, name="stake", desc="PDA of the owner stake"]
+   * #[account(2), signer, name="owner", desc="fraction owner"]
+   * #[account(3), read, name="fraction_mint", desc="fungible mint of the fractions"]
+   * #[account(4), writable, name="owner_token_account", desc="fractions returned to"]
+   * #[account(5), writable, name="escrow_token_account", desc="fraction token account owned by the distributor"]
+   * #[account(6), read, name="token_program"]
+   */
+  WithdrawFractions(FractionAmountArgs),
+  /* Claim Fractional Royalties
+   * pay the owner the lamports their staked fractions earned. with the royalty vault the
+   * balance of the distributor there is swept in first.
+   * #[account(0), writable, name="distributor", desc="PDA of the distributor"]
+   * #[account(1), writable, name="stake", desc="PDA of the owner stake"]
+   * #[account(2), signer & writable, name="owner", desc="fraction owner receiving the lamports"]
+   * #[account(3), writable, optional, name="vault", desc="royalty vault PDA of the NFT mint"]
+   */
+  ClaimFractionalRoyalties(),
 
 }
 
@@ -123,6 +504,28 @@ impl ReeMetadataInstruction {
       2 => Self::LockNFT(),
       3 => Self::AddRoyalty(Self::unpack_add_royalty_args(rest)?),
       4 => Self::NftTransaction(Self::unpack_nft_transaction_args(rest)?),
+      5 => Self::CreateRoyaltyVault(),
+      6 => Self::ClaimRoyalties(),
+      7 => Self::SetPrimarySplit(Self::unpack_set_primary_split_args(rest)?),
+      8 => Self::Transfer(),
+      9 => Self::Buy(Self::unpack_nft_transaction_args(rest)?),
+      10 => Self::ImportFromMetaplex(),
+      11 => Self::SetAttribute(Self::unpack_set_attribute_args(rest)?),
+      12 => Self::RemoveAttribute(Self::unpack_remove_attribute_args(rest)?),
+      13 => Self::InitMultisig(Self::unpack_init_multisig_args(rest)?),
+      14 => Self::ApproveDelegate(Self::unpack_approve_delegate_args(rest)?),
+      15 => Self::RevokeDelegate(),
+      16 => Self::UpdateUri(Self::unpack_update_uri_args(rest)?),
+      17 => Self::InitConfig(Self::unpack_config_args(rest)?),
+      18 => Self::UpdateConfig(Self::unpack_config_args(rest)?),
+      19 => Self::SetPaused(Self::unpack_set_paused_args(rest)?),
+      20 => Self::QuoteRoyalty(Self::unpack_quote_royalty_args(rest)?),
+      21 => Self::UpdateStats(Self::unpack_update_stats_args(rest)?),
+      22 => Self::Revoke(),
+      23 => Self::Fractionalize(Self::unpack_fractionalize_args(rest)?),
+      24 => Self::DepositFractions(Self::unpack_fraction_amount_args(rest)?),
+      25 => Self::WithdrawFractions(Self::unpack_fraction_amount_args(rest)?),
+      26 => Self::ClaimFractionalRoyalties(),
       _ => return Err(InvalidInstruction.into())
     })
   }
@@ -149,9 +552,21 @@ impl ReeMetadataInstruction {
     msg!("get uri");
     let (uri, rest) = unpack_string(rest).ok_or(InvalidInstruction)?;
     msg!("get resale");
-    let (resale_u16, _rest) = rest.split_at(2); 
+    if rest.len() < 2 {
+      return Err(InvalidInstruction.into())
+    }
+    let (resale_u16, rest) = rest.split_at(2); 
     let resale_fee = resale_u16.try_into().ok()
       .map(u16::from_le_bytes).ok_or(InvalidInstruction)?;
+    // older clients end the data at the resale fee
+    let (enforced, rest) = match rest.split_first() {
+      None => (false, rest),
+      Some((enforced, rest)) => (*enforced != 0, rest),
+    };
+    let soulbound = match rest.first() {
+      None => false,
+      Some(soulbound) => *soulbound != 0,
+    };
     
     msg!("create aar");
     let aar = MetadataArgsRRA{
@@ -159,6 +574,8 @@ impl ReeMetadataInstruction {
       symbol: symbol,
       uri: uri,
       resale_fee: resale_fee,
+      enforced: enforced,
+      soulbound: soulbound,
     };
 
     Ok(CreateMetadataArgs{metadata: metadata, aar_data: aar})
@@ -170,12 +587,172 @@ impl ReeMetadataInstruction {
     Ok(AddRoyaltyArgs{share})
   }
 
+  fn unpack_set_primary_split_args(data: &[u8]) -> Result<SetPrimarySplitArgs, ProgramError> {
+    if data.len() < 4 {
+      return Err(InvalidInstruction.into())
+    }
+    let (len_chunk, mut rest) = data.split_at(4);
+    let len = len_chunk.try_into().ok()
+      .map(u32::from_le_bytes).ok_or(InvalidInstruction)?;
+
+    let mut shares: Vec<u16> = Vec::new();
+    for _ in 0..len {
+      if rest.len() < 2 {
+        return Err(InvalidInstruction.into())
+      }
+      let (share_u16, next) = rest.split_at(2);
+      shares.push(share_u16.try_into().ok()
+        .map(u16::from_le_bytes).ok_or(InvalidInstruction)?);
+      rest = next;
+    }
+
+    Ok(SetPrimarySplitArgs{shares})
+  }
+
+  fn unpack_set_attribute_args(data: &[u8]) -> Result<SetAttributeArgs, ProgramError> {
+    let (key, rest) = unpack_string(data).ok_or(InvalidInstruction)?;
+    let (value, _rest) = unpack_string(rest).ok_or(InvalidInstruction)?;
+    Ok(SetAttributeArgs{key, value})
+  }
+
+  fn unpack_remove_attribute_args(data: &[u8]) -> Result<RemoveAttributeArgs, ProgramError> {
+    let (key, _rest) = unpack_string(data).ok_or(InvalidInstruction)?;
+    Ok(RemoveAttributeArgs{key})
+  }
+
+  fn unpack_init_multisig_args(data: &[u8]) -> Result<InitMultisigArgs, ProgramError> {
+    let (threshold, _rest) = data.split_first().ok_or(InvalidInstruction)?;
+    Ok(InitMultisigArgs{threshold: *threshold})
+  }
+
+  fn unpack_approve_delegate_args(data: &[u8]) -> Result<ApproveDelegateArgs, ProgramError> {
+    let (scope, rest) = data.split_first().ok_or(InvalidInstruction)?;
+    let expiry: i64 = rest.get(..8).and_then(|slice| slice.try_into().ok())
+      .map(i64::from_le_bytes).ok_or(InvalidInstruction)?;
+
+    Ok(ApproveDelegateArgs{scope: *scope, expiry})
+  }
+
+  fn unpack_update_uri_args(data: &[u8]) -> Result<UpdateUriArgs, ProgramError> {
+    let (uri, _rest) = unpack_string(data).ok_or(InvalidInstruction)?;
+    Ok(UpdateUriArgs{uri})
+  }
+
+  fn unpack_config_args(data: &[u8]) -> Result<ConfigArgs, ProgramError> {
+    let protocol_fee: u16 = data.get(..2).and_then(|slice| slice.try_into().ok())
+      .map(u16::from_le_bytes).ok_or(InvalidInstruction)?;
+    Ok(ConfigArgs{protocol_fee})
+  }
+
+  fn unpack_set_paused_args(data: &[u8]) -> Result<SetPausedArgs, ProgramError> {
+    let (paused, _rest) = data.split_first().ok_or(InvalidInstruction)?;
+    Ok(SetPausedArgs{paused: *paused != 0})
+  }
+
+  fn unpack_quote_royalty_args(data: &[u8]) -> Result<QuoteRoyaltyArgs, ProgramError> {
+    let amount: u64 = data.get(..8).and_then(|slice| slice.try_into().ok())
+      .map(u64::from_le_bytes).ok_or(InvalidInstruction)?;
+    Ok(QuoteRoyaltyArgs{amount})
+  }
+
+  fn unpack_stat_value(data: &[u8]) -> Result<(StatValue, &[u8]), ProgramError> {
+    let (variant, rest) = data.split_first().ok_or(InvalidInstruction)?;
+    Ok(match variant {
+      0 => {
+        if rest.len() < 8 {
+          return Err(InvalidInstruction.into())
+        }
+        let (value, rest) = rest.split_at(8);
+        let value = value.try_into().ok()
+          .map(i64::from_le_bytes).ok_or(InvalidInstruction)?;
+        (StatValue::Int(value), rest)
+      },
+      1 => {
+        let (value, rest) = rest.split_first().ok_or(InvalidInstruction)?;
+        (StatValue::Bool(*value != 0), rest)
+      },
+      2 => {
+        let (value, rest) = unpack_string(rest).ok_or(InvalidInstruction)?;
+        (StatValue::Text(value), rest)
+      },
+      _ => return Err(InvalidInstruction.into())
+    })
+  }
+
+  fn unpack_update_stats_args(data: &[u8]) -> Result<UpdateStatsArgs, ProgramError> {
+    if data.len() < 4 {
+      return Err(InvalidInstruction.into())
+    }
+    let (len_chunk, mut rest) = data.split_at(4);
+    let len = len_chunk.try_into().ok()
+      .map(u32::from_le_bytes).ok_or(InvalidInstruction)?;
+
+    let mut changes: Vec<StatChange> = Vec::new();
+    for _ in 0..len {
+      let (key, next) = unpack_string(rest).ok_or(InvalidInstruction)?;
+      let (has_value, next) = next.split_first().ok_or(InvalidInstruction)?;
+      let (value, next) = match has_value {
+        0 => (None, next),
+        _ => {
+          let (value, next) = Self::unpack_stat_value(next)?;
+          (Some(value), next)
+        }
+      };
+      changes.push(StatChange{key, value});
+      rest = next;
+    }
+
+    Ok(UpdateStatsArgs{changes})
+  }
+
+  fn unpack_fractionalize_args(data: &[u8]) -> Result<FractionalizeArgs, ProgramError> {
+    let supply: u64 = data.get(..8).and_then(|slice| slice.try_into().ok())
+      .map(u64::from_le_bytes).ok_or(InvalidInstruction)?;
+    Ok(FractionalizeArgs{supply})
+  }
+
+  fn unpack_fraction_amount_args(data: &[u8]) -> Result<FractionAmountArgs, ProgramError> {
+    let amount: u64 = data.get(..8).and_then(|slice| slice.try_into().ok())
+      .map(u64::from_le_bytes).ok_or(InvalidInstruction)?;
+    Ok(FractionAmountArgs{amount})
+  }
+
   fn unpack_nft_transaction_args(data: &[u8]) -> Result<NftTransactionArgs, ProgramError> {
-    let amount: u64 = data.try_into().ok()
+    if data.len() < 8 {
+      return Err(InvalidInstruction.into())
+    }
+    let (amount_u64, rest) = data.split_at(8);
+    let amount: u64 = amount_u64.try_into().ok()
       .map(u64::from_le_bytes).ok_or(InvalidInstruction)?;
-    
-    Ok(NftTransactionArgs{amount})
+
+    // older clients end the data at the amount
+    let (marketplace_fee, rest) = Self::unpack_sale_fee(rest)?;
+    let (referral_fee, _rest) = Self::unpack_sale_fee(rest)?;
+
+    Ok(NftTransactionArgs{amount, marketplace_fee, referral_fee})
+  }
+
+  fn unpack_sale_fee(data: &[u8]) -> Result<(Option<SaleFee>, &[u8]), ProgramError> {
+    let (is_some, rest) = match data.split_first() {
+      None => return Ok((None, data)),
+      Some(split) => split,
+    };
+    if *is_some == 0 {
+      return Ok((None, rest))
+    }
+
+    if rest.len() < 34 {
+      return Err(InvalidInstruction.into())
+    }
+    let (recipient, rest) = rest.split_at(32);
+    let (basis_points, rest) = rest.split_at(2);
+    let recipient = Pubkey::try_from(recipient).map_err(|_| InvalidInstruction)?;
+    let basis_points = basis_points.try_into().ok()
+      .map(u16::from_le_bytes).ok_or(InvalidInstruction)?;
+
+    Ok((Some(SaleFee{recipient, basis_points}), rest))
   }
+
 }
 
 pub fn mint_nft(
@@ -199,28 +776,670 @@ pub fn mint_nft(
 
 pub fn nft_funding_sol(
   program_id: &Pubkey,
+  token_program: &Pubkey,
   metadata_pda: &Pubkey,
   payer: &Pubkey,
   target: &Pubkey,
+  mint: &Pubkey,
+  freeze_authority: &Pubkey,
+  seller_token_account: &Pubkey,
+  buyer_token_account: &Pubkey,
   royalties: Vec<Pubkey>,
+  vault: Option<&Pubkey>,
+  protocol_fee_recipient: Option<&Pubkey>,
   data: NftTransactionArgs,
 ) -> Instruction {
   let mut accounts = vec![
     AccountMeta::new(*metadata_pda, false),
     AccountMeta::new(*payer, true),
-    AccountMeta::new(*target, false),
-    AccountMeta::new_readonly(solana_program::system_program::id(), false)
+    AccountMeta::new(*target, true),
+    AccountMeta::new_readonly(solana_program::system_program::id(), false),
+    AccountMeta::new_readonly(*mint, false),
+    AccountMeta::new_readonly(*freeze_authority, false),
+    AccountMeta::new(*seller_token_account, false),
+    AccountMeta::new(*buyer_token_account, false),
+    AccountMeta::new_readonly(*token_program, false),
   ];
+
+  for fee in [&data.marketplace_fee, &data.referral_fee].into_iter().flatten() {
+    accounts.push(
+      AccountMeta::new(fee.recipient, false)
+    );
+  }
   
   for account in royalties.iter() {
     accounts.push(
       AccountMeta::new(*account, false)
     );
   }
-  
+
+  // needed when the vault is enabled or a payout isn't rent safe and is carried to it
+  if let Some(vault) = vault {
+    accounts.push(
+      AccountMeta::new(*vault, false)
+    );
+  }
+
+  // found by key, after the royalty accounts and the vault
+  accounts.push(
+    AccountMeta::new_readonly(find_config_address(program_id).0, false)
+  );
+  if let Some(protocol_fee_recipient) = protocol_fee_recipient {
+    accounts.push(
+      AccountMeta::new(*protocol_fee_recipient, false)
+    );
+  }
+
   Instruction {
     program_id: *program_id,
     accounts,
-    data: ReeMetadataInstruction::NftTransaction((data)).try_to_vec().unwrap()
+    data: ReeMetadataInstruction::NftTransaction(data).try_to_vec().unwrap()
   }
-}
\ No newline at end of file
+}
+
+pub fn create_royalty_vault(
+  program_id: &Pubkey,
+  metadata_pda: &Pubkey,
+  payer: &Pubkey,
+  update_authority: &Pubkey,
+  vault_pda: &Pubkey,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new(*metadata_pda, false),
+      AccountMeta::new(*payer, true),
+      AccountMeta::new_readonly(*update_authority, true),
+      AccountMeta::new(*vault_pda, false),
+      AccountMeta::new_readonly(solana_program::system_program::id(), false),
+      AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+    ],
+    data: ReeMetadataInstruction::CreateRoyaltyVault().try_to_vec().unwrap()
+  }
+}
+
+pub fn claim_royalties(
+  program_id: &Pubkey,
+  vault_pda: &Pubkey,
+  holder: &Pubkey,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new(*vault_pda, false),
+      AccountMeta::new(*holder, true),
+    ],
+    data: ReeMetadataInstruction::ClaimRoyalties().try_to_vec().unwrap()
+  }
+}
+
+pub fn set_primary_split(
+  program_id: &Pubkey,
+  metadata_pda: &Pubkey,
+  payer: &Pubkey,
+  update_authority: &Pubkey,
+  split: Vec<(Pubkey, u16)>,
+) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new(*metadata_pda, false),
+    AccountMeta::new(*payer, true),
+    AccountMeta::new_readonly(*update_authority, true),
+    AccountMeta::new_readonly(solana_program::system_program::id(), false),
+    AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+  ];
+
+  for (account, _) in split.iter() {
+    accounts.push(
+      AccountMeta::new_readonly(*account, false)
+    );
+  }
+
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data: ReeMetadataInstruction::SetPrimarySplit(SetPrimarySplitArgs{
+      shares: split.iter().map(|(_, share)| *share).collect(),
+    }).try_to_vec().unwrap()
+  }
+}
+
+pub fn transfer(
+  program_id: &Pubkey,
+  token_program: &Pubkey,
+  metadata_pda: &Pubkey,
+  update_authority: &Pubkey,
+  mint: &Pubkey,
+  freeze_authority: &Pubkey,
+  owner: &Pubkey,
+  source: &Pubkey,
+  destination: &Pubkey,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new_readonly(*metadata_pda, false),
+      AccountMeta::new_readonly(*update_authority, true),
+      AccountMeta::new_readonly(*mint, false),
+      AccountMeta::new_readonly(*freeze_authority, false),
+      AccountMeta::new_readonly(*owner, true),
+      AccountMeta::new(*source, false),
+      AccountMeta::new(*destination, false),
+      AccountMeta::new_readonly(*token_program, false),
+    ],
+    data: ReeMetadataInstruction::Transfer().try_to_vec().unwrap()
+  }
+}
+
+pub fn buy(
+  program_id: &Pubkey,
+  token_program: &Pubkey,
+  metadata_pda: &Pubkey,
+  payer: &Pubkey,
+  target: &Pubkey,
+  mint: &Pubkey,
+  freeze_authority: &Pubkey,
+  seller_token_account: &Pubkey,
+  buyer_token_account: &Pubkey,
+  royalties: Vec<Pubkey>,
+  vault: Option<&Pubkey>,
+  protocol_fee_recipient: Option<&Pubkey>,
+  data: NftTransactionArgs,
+) -> Instruction {
+  // same accounts as NftTransaction
+  let buy_data = ReeMetadataInstruction::Buy(data.clone()).try_to_vec().unwrap();
+  let mut instruction = nft_funding_sol(
+    program_id,
+    token_program,
+    metadata_pda,
+    payer,
+    target,
+    mint,
+    freeze_authority,
+    seller_token_account,
+    buyer_token_account,
+    royalties,
+    vault,
+    protocol_fee_recipient,
+    data,
+  );
+  instruction.data = buy_data;
+  instruction
+}
+
+pub fn import_from_metaplex(
+  program_id: &Pubkey,
+  metadata_pda: &Pubkey,
+  mint: &Pubkey,
+  metaplex_metadata: &Pubkey,
+  payer: &Pubkey,
+  update_authority: &Pubkey,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new(*metadata_pda, false),
+      AccountMeta::new_readonly(*mint, false),
+      AccountMeta::new_readonly(*metaplex_metadata, false),
+      AccountMeta::new(*payer, true),
+      AccountMeta::new_readonly(*update_authority, true),
+      AccountMeta::new_readonly(solana_program::system_program::id(), false),
+      AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+      AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ],
+    data: ReeMetadataInstruction::ImportFromMetaplex().try_to_vec().unwrap()
+  }
+}
+
+pub fn set_attribute(
+  program_id: &Pubkey,
+  metadata_pda: &Pubkey,
+  payer: &Pubkey,
+  update_authority: &Pubkey,
+  data: SetAttributeArgs,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new(*metadata_pda, false),
+      AccountMeta::new(*payer, true),
+      AccountMeta::new_readonly(*update_authority, true),
+      AccountMeta::new_readonly(solana_program::system_program::id(), false),
+      AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+    ],
+    data: ReeMetadataInstruction::SetAttribute(data).try_to_vec().unwrap()
+  }
+}
+
+pub fn remove_attribute(
+  program_id: &Pubkey,
+  metadata_pda: &Pubkey,
+  payer: &Pubkey,
+  update_authority: &Pubkey,
+  data: RemoveAttributeArgs,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new(*metadata_pda, false),
+      AccountMeta::new(*payer, true),
+      AccountMeta::new_readonly(*update_authority, true),
+      AccountMeta::new_readonly(solana_program::system_program::id(), false),
+      AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+    ],
+    data: ReeMetadataInstruction::RemoveAttribute(data).try_to_vec().unwrap()
+  }
+}
+
+pub fn init_multisig(
+  program_id: &Pubkey,
+  multisig_pda: &Pubkey,
+  payer: &Pubkey,
+  base: &Pubkey,
+  signers: Vec<Pubkey>,
+  threshold: u8,
+) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new(*multisig_pda, false),
+    AccountMeta::new(*payer, true),
+    AccountMeta::new_readonly(*base, true),
+    AccountMeta::new_readonly(solana_program::system_program::id(), false),
+    AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+  ];
+
+  for signer in signers.iter() {
+    accounts.push(
+      AccountMeta::new_readonly(*signer, false)
+    );
+  }
+
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data: ReeMetadataInstruction::InitMultisig(InitMultisigArgs{threshold}).try_to_vec().unwrap()
+  }
+}
+
+pub fn approve_delegate(
+  program_id: &Pubkey,
+  metadata_pda: &Pubkey,
+  payer: &Pubkey,
+  update_authority: &Pubkey,
+  delegate: &Pubkey,
+  delegate_record_pda: &Pubkey,
+  scope: u8,
+  expiry: i64,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new_readonly(*metadata_pda, false),
+      AccountMeta::new(*payer, true),
+      AccountMeta::new_readonly(*update_authority, true),
+      AccountMeta::new_readonly(*delegate, false),
+      AccountMeta::new(*delegate_record_pda, false),
+      AccountMeta::new_readonly(solana_program::system_program::id(), false),
+      AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+    ],
+    data: ReeMetadataInstruction::ApproveDelegate(ApproveDelegateArgs{scope, expiry}).try_to_vec().unwrap()
+  }
+}
+
+pub fn revoke_delegate(
+  program_id: &Pubkey,
+  metadata_pda: &Pubkey,
+  authority: &Pubkey,
+  delegate_record_pda: &Pubkey,
+  receiver: &Pubkey,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new_readonly(*metadata_pda, false),
+      AccountMeta::new_readonly(*authority, true),
+      AccountMeta::new(*delegate_record_pda, false),
+      AccountMeta::new(*receiver, false),
+    ],
+    data: ReeMetadataInstruction::RevokeDelegate().try_to_vec().unwrap()
+  }
+}
+
+pub fn update_uri(
+  program_id: &Pubkey,
+  metadata_pda: &Pubkey,
+  payer: &Pubkey,
+  authority: &Pubkey,
+  delegate_record_pda: Option<&Pubkey>,
+  uri: String,
+) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new(*metadata_pda, false),
+    AccountMeta::new(*payer, true),
+    AccountMeta::new_readonly(*authority, true),
+    AccountMeta::new_readonly(solana_program::system_program::id(), false),
+    AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+  ];
+
+  if let Some(delegate_record_pda) = delegate_record_pda {
+    accounts.push(
+      AccountMeta::new_readonly(*delegate_record_pda, false)
+    );
+  }
+
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data: ReeMetadataInstruction::UpdateUri(UpdateUriArgs{uri}).try_to_vec().unwrap()
+  }
+}
+
+pub fn init_config(
+  program_id: &Pubkey,
+  payer: &Pubkey,
+  admin: &Pubkey,
+  fee_recipient: &Pubkey,
+  protocol_fee: u16,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new(find_config_address(program_id).0, false),
+      AccountMeta::new(*payer, true),
+      AccountMeta::new_readonly(*admin, true),
+      AccountMeta::new_readonly(*fee_recipient, false),
+      AccountMeta::new_readonly(solana_program::system_program::id(), false),
+      AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+      AccountMeta::new_readonly(
+        Pubkey::find_program_address(&[program_id.as_ref()], &solana_program::bpf_loader_upgradeable::id()).0,
+        false
+      ),
+    ],
+    data: ReeMetadataInstruction::InitConfig(ConfigArgs{protocol_fee}).try_to_vec().unwrap()
+  }
+}
+
+pub fn update_config(
+  program_id: &Pubkey,
+  admin: &Pubkey,
+  new_admin: &Pubkey,
+  fee_recipient: &Pubkey,
+  protocol_fee: u16,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new(find_config_address(program_id).0, false),
+      AccountMeta::new_readonly(*admin, true),
+      AccountMeta::new_readonly(*new_admin, false),
+      AccountMeta::new_readonly(*fee_recipient, false),
+    ],
+    data: ReeMetadataInstruction::UpdateConfig(ConfigArgs{protocol_fee}).try_to_vec().unwrap()
+  }
+}
+
+pub fn set_paused(
+  program_id: &Pubkey,
+  admin: &Pubkey,
+  paused: bool,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new(find_config_address(program_id).0, false),
+      AccountMeta::new_readonly(*admin, true),
+    ],
+    data: ReeMetadataInstruction::SetPaused(SetPausedArgs{paused}).try_to_vec().unwrap()
+  }
+}
+
+pub fn create_metadata(
+  program_id: &Pubkey,
+  token_program: &Pubkey,
+  metadata_pda: &Pubkey,
+  mint: &Pubkey,
+  royalty_owner: &Pubkey,
+  mint_authority: &Pubkey,
+  new_mint_authority: &Pubkey,
+  payer: &Pubkey,
+  update_authority: Option<&Pubkey>,
+  collection: Option<&Pubkey>,
+  data: CreateMetadataArgs,
+) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new(*metadata_pda, false),
+    AccountMeta::new(*mint, false),
+    AccountMeta::new_readonly(*royalty_owner, false),
+    AccountMeta::new_readonly(*mint_authority, true),
+    AccountMeta::new_readonly(*new_mint_authority, false),
+    AccountMeta::new(*payer, true),
+  ];
+
+  // must be given unless the update type is None
+  if let Some(update_authority) = update_authority {
+    accounts.push(
+      AccountMeta::new_readonly(*update_authority, false)
+    );
+  }
+
+  accounts.push(AccountMeta::new_readonly(solana_program::system_program::id(), false));
+  accounts.push(AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false));
+  accounts.push(AccountMeta::new_readonly(*token_program, false));
+
+  if let Some(collection) = collection {
+    accounts.push(
+      AccountMeta::new_readonly(*collection, false)
+    );
+  }
+
+  accounts.push(
+    AccountMeta::new_readonly(find_config_address(program_id).0, false)
+  );
+
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data: ReeMetadataInstruction::CreateMetaData(data).try_to_vec().unwrap()
+  }
+}
+
+pub fn lock_nft(
+  program_id: &Pubkey,
+  metadata_pda: &Pubkey,
+  payer: &Pubkey,
+  update_authority: &Pubkey,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new(*metadata_pda, false),
+      AccountMeta::new_readonly(*payer, true),
+      AccountMeta::new_readonly(*update_authority, true),
+    ],
+    data: ReeMetadataInstruction::LockNFT().try_to_vec().unwrap()
+  }
+}
+
+pub fn add_royalty(
+  program_id: &Pubkey,
+  metadata_pda: &Pubkey,
+  payer: &Pubkey,
+  update_authority: &Pubkey,
+  new_royalty: &Pubkey,
+  first_royalty: &Pubkey,
+  share: u16,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new(*metadata_pda, false),
+      AccountMeta::new(*payer, true),
+      AccountMeta::new_readonly(*update_authority, true),
+      AccountMeta::new_readonly(*new_royalty, false),
+      AccountMeta::new_readonly(solana_program::system_program::id(), false),
+      AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+      AccountMeta::new_readonly(*first_royalty, false),
+    ],
+    data: ReeMetadataInstruction::AddRoyalty(AddRoyaltyArgs{share}).try_to_vec().unwrap()
+  }
+}
+
+pub fn quote_royalty(
+  program_id: &Pubkey,
+  metadata_pda: &Pubkey,
+  amount: u64,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new_readonly(*metadata_pda, false),
+      AccountMeta::new_readonly(find_config_address(program_id).0, false),
+    ],
+    data: ReeMetadataInstruction::QuoteRoyalty(QuoteRoyaltyArgs{amount}).try_to_vec().unwrap()
+  }
+}
+
+pub fn update_stats(
+  program_id: &Pubkey,
+  metadata_pda: &Pubkey,
+  payer: &Pubkey,
+  game_authority: &Pubkey,
+  changes: Vec<StatChange>,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new(*metadata_pda, false),
+      AccountMeta::new(*payer, true),
+      AccountMeta::new_readonly(*game_authority, true),
+      AccountMeta::new_readonly(solana_program::system_program::id(), false),
+      AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+    ],
+    data: ReeMetadataInstruction::UpdateStats(UpdateStatsArgs{changes}).try_to_vec().unwrap()
+  }
+}
+
+pub fn revoke(
+  program_id: &Pubkey,
+  metadata_pda: &Pubkey,
+  update_authority: &Pubkey,
+  mint: &Pubkey,
+  freeze_authority: &Pubkey,
+  token_account: &Pubkey,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new_readonly(*metadata_pda, false),
+      AccountMeta::new_readonly(*update_authority, true),
+      AccountMeta::new(*mint, false),
+      AccountMeta::new_readonly(*freeze_authority, false),
+      AccountMeta::new(*token_account, false),
+      AccountMeta::new_readonly(spl_token_2022::id(), false),
+    ],
+    data: ReeMetadataInstruction::Revoke().try_to_vec().unwrap()
+  }
+}
+
+pub fn fractionalize(
+  program_id: &Pubkey,
+  token_program: &Pubkey,
+  metadata_pda: &Pubkey,
+  nft_mint: &Pubkey,
+  payer: &Pubkey,
+  holder: &Pubkey,
+  fraction_mint: &Pubkey,
+  holder_token_account: &Pubkey,
+  supply: u64,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new(*metadata_pda, false),
+      AccountMeta::new(*payer, true),
+      AccountMeta::new_readonly(*holder, true),
+      AccountMeta::new(find_distributor_address(program_id, nft_mint, holder).0, false),
+      AccountMeta::new(*fraction_mint, false),
+      AccountMeta::new(*holder_token_account, false),
+      AccountMeta::new_readonly(solana_program::system_program::id(), false),
+      AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+      AccountMeta::new_readonly(*token_program, false),
+    ],
+    data: ReeMetadataInstruction::Fractionalize(FractionalizeArgs{supply}).try_to_vec().unwrap()
+  }
+}
+
+pub fn deposit_fractions(
+  program_id: &Pubkey,
+  token_program: &Pubkey,
+  distributor: &Pubkey,
+  owner: &Pubkey,
+  fraction_mint: &Pubkey,
+  owner_token_account: &Pubkey,
+  escrow_token_account: &Pubkey,
+  amount: u64,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new(*distributor, false),
+      AccountMeta::new(find_fraction_stake_address(program_id, distributor, owner).0, false),
+      AccountMeta::new(*owner, true),
+      AccountMeta::new_readonly(*fraction_mint, false),
+      AccountMeta::new(*owner_token_account, false),
+      AccountMeta::new(*escrow_token_account, false),
+      AccountMeta::new_readonly(*token_program, false),
+      AccountMeta::new_readonly(solana_program::system_program::id(), false),
+      AccountMeta::new_readonly(solana_program::sysvar::rent::id(), false),
+    ],
+    data: ReeMetadataInstruction::DepositFractions(FractionAmountArgs{amount}).try_to_vec().unwrap()
+  }
+}
+
+pub fn withdraw_fractions(
+  program_id: &Pubkey,
+  token_program: &Pubkey,
+  distributor: &Pubkey,
+  owner: &Pubkey,
+  fraction_mint: &Pubkey,
+  owner_token_account: &Pubkey,
+  escrow_token_account: &Pubkey,
+  amount: u64,
+) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new(*distributor, false),
+      AccountMeta::new(find_fraction_stake_address(program_id, distributor, owner).0, false),
+      AccountMeta::new_readonly(*owner, true),
+      AccountMeta::new_readonly(*fraction_mint, false),
+      AccountMeta::new(*owner_token_account, false),
+      AccountMeta::new(*escrow_token_account, false),
+      AccountMeta::new_readonly(*token_program, false),
+    ],
+    data: ReeMetadataInstruction::WithdrawFractions(FractionAmountArgs{amount}).try_to_vec().unwrap()
+  }
+}
+
+pub fn claim_fractional_royalties(
+  program_id: &Pubkey,
+  distributor: &Pubkey,
+  owner: &Pubkey,
+  vault_pda: Option<&Pubkey>,
+) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new(*distributor, false),
+    AccountMeta::new(find_fraction_stake_address(program_id, distributor, owner).0, false),
+    AccountMeta::new(*owner, true),
+  ];
+
+  if let Some(vault_pda) = vault_pda {
+    accounts.push(
+      AccountMeta::new(*vault_pda, false)
+    );
+  }
+
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data: ReeMetadataInstruction::ClaimFractionalRoyalties().try_to_vec().unwrap()
+  }
+}
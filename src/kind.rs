@@ -0,0 +1,532 @@
+use crate::{
+  error::ReeMetaError,
+  instruction::{
+    CreateMetadataArgs,
+    MetadataArgs,
+    AddRoyaltyArgs,
+    NftTransactionArgs,
+    SetPrimarySplitArgs,
+    SetAttributeArgs,
+    RemoveAttributeArgs,
+    UpdateUriArgs,
+    QuoteRoyaltyArgs,
+    UpdateStatsArgs,
+    FractionalizeArgs,
+  },
+  state::{
+    Metadata,
+    MetadataData,
+    ArtNft,
+    CustomNft,
+    GameItem,
+    Kind,
+    MultisigConfig,
+    UpdateType,
+    PREFIX,
+    SCOPE_LOCK,
+  },
+  utils::{
+    assert_buyer,
+    assert_metadata_address,
+    assert_nft_transfer,
+    assert_owned_by,
+    assert_token_program,
+    assert_update_authority_or_delegate,
+    assert_valid_mint_authority,
+    create_pda_account,
+    transfer_nft,
+    unpack_mint,
+  },
+};
+use borsh::{BorshSerialize, BorshDeserialize};
+
+use solana_program::{
+  account_info::{AccountInfo, next_account_info},
+  entrypoint::ProgramResult,
+  msg,
+  program::invoke,
+  program_error::ProgramError,
+  pubkey::Pubkey,
+  system_instruction,
+  system_program,
+  sysvar::{rent::{Rent, ID as RENT_ID}, Sysvar},
+};
+use spl_token_2022::state::Mint;
+
+use std::marker::PhantomData;
+
+/// an NFT kind, the data stored after the metadata fields and how each instruction treats it.
+/// a kind implements the handlers it supports, the rest refuse with InvalidNFTKind. lock and
+/// a sale paying the full amount to the seller are shared by every kind unless overridden.
+/// the metadata account is always the first account
+pub trait NftKind: MetadataData + BorshSerialize + BorshDeserialize + PartialEq + Clone {
+  const KIND: Kind;
+  const NAME: &'static str;
+
+  fn create(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _data: CreateMetadataArgs,
+  ) -> ProgramResult {
+    unsupported(Self::NAME, "can not be created with CreateMetaData")
+  }
+
+  fn lock_nft(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata: Metadata<Self>,
+  ) -> ProgramResult {
+    lock_metadata(program_id, accounts, metadata)
+  }
+
+  fn add_royalty(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _metadata: Metadata<Self>,
+    _data: AddRoyaltyArgs,
+  ) -> ProgramResult {
+    unsupported(Self::NAME, "has no royalties")
+  }
+
+  fn set_primary_split(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _metadata: Metadata<Self>,
+    _data: SetPrimarySplitArgs,
+  ) -> ProgramResult {
+    unsupported(Self::NAME, "has no royalties")
+  }
+
+  fn create_royalty_vault(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _metadata: Metadata<Self>,
+  ) -> ProgramResult {
+    unsupported(Self::NAME, "has no royalties")
+  }
+
+  fn quote_royalty(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _metadata: Metadata<Self>,
+    _data: QuoteRoyaltyArgs,
+  ) -> ProgramResult {
+    unsupported(Self::NAME, "has no royalties")
+  }
+
+  fn nft_transaction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    metadata: Metadata<Self>,
+    data: NftTransactionArgs,
+  ) -> ProgramResult {
+    sell_metadata(program_id, accounts, metadata, data)
+  }
+
+  fn transfer(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _metadata: Metadata<Self>,
+  ) -> ProgramResult {
+    unsupported(Self::NAME, "can not be transferred through ree-meta")
+  }
+
+  fn set_attribute(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _metadata: Metadata<Self>,
+    _data: SetAttributeArgs,
+  ) -> ProgramResult {
+    unsupported(Self::NAME, "has no attributes")
+  }
+
+  fn remove_attribute(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _metadata: Metadata<Self>,
+    _data: RemoveAttributeArgs,
+  ) -> ProgramResult {
+    unsupported(Self::NAME, "has no attributes")
+  }
+
+  fn update_uri(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _metadata: Metadata<Self>,
+    _data: UpdateUriArgs,
+  ) -> ProgramResult {
+    unsupported(Self::NAME, "has no uri")
+  }
+
+  fn update_stats(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _metadata: Metadata<Self>,
+    _data: UpdateStatsArgs,
+  ) -> ProgramResult {
+    unsupported(Self::NAME, "has no stats")
+  }
+
+  fn revoke(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _metadata: Metadata<Self>,
+  ) -> ProgramResult {
+    unsupported(Self::NAME, "can not be revoked")
+  }
+
+  fn fractionalize(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _metadata: Metadata<Self>,
+    _data: FractionalizeArgs,
+  ) -> ProgramResult {
+    unsupported(Self::NAME, "has no royalties")
+  }
+}
+
+fn unsupported(name: &str, reason: &str) -> ProgramResult {
+  msg!("This NFT Kind ({}) {}", name, reason);
+  Err(ReeMetaError::InvalidNFTKind.into())
+}
+
+/// lock the metadata so it can't be modified anymore
+pub fn lock_metadata<T: NftKind>(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  mut metadata: Metadata<T>,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+  let _payer_account_info = next_account_info(account_iter)?;
+  let update_authority_account_info = next_account_info(account_iter)?;
+
+  if !metadata.is_modifiable {
+    return Err(ReeMetaError::AlreadyLocked.into())
+  }
+
+  assert_update_authority_or_delegate(
+    program_id,
+    metadata_account_info.key,
+    &metadata.update_type,
+    &metadata.update_authority,
+    update_authority_account_info,
+    accounts,
+    SCOPE_LOCK,
+  )?;
+
+  // validated data lock the NFT. only the header is written so metadata from before
+  // the data's appended fields doesn't need room for them
+  metadata.is_modifiable = false;
+
+  metadata.header().serialize(&mut *metadata_account_info.data.borrow_mut())?;
+  Ok(())
+}
+
+/// sell the NFT without a royalty system, the full amount goes to the target
+pub fn sell_metadata<T: NftKind>(
+  _program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  metadata: Metadata<T>,
+  data: NftTransactionArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let _metadata_account_info = next_account_info(account_iter)?;
+  let payer_account_info = next_account_info(account_iter)?;
+  let target_account_info = next_account_info(account_iter)?;
+  let system_info = next_account_info(account_iter)?;
+  let mint_account_info = next_account_info(account_iter)?;
+  let _freeze_authority_account_info = next_account_info(account_iter)?;
+  let seller_token_account_info = next_account_info(account_iter)?;
+  let buyer_token_account_info = next_account_info(account_iter)?;
+  let token_program_info = next_account_info(account_iter)?;
+
+  if *system_info.key != system_program::ID {
+    msg!("Invalid system account");
+    return Err(ReeMetaError::InvalidInstruction.into())
+  }
+
+  let (mint, _, _) = assert_nft_transfer(
+    &metadata.mint,
+    mint_account_info,
+    target_account_info,
+    seller_token_account_info,
+    buyer_token_account_info,
+    token_program_info,
+  )?;
+  assert_buyer(
+    payer_account_info,
+    target_account_info,
+    buyer_token_account_info,
+    token_program_info.key,
+  )?;
+
+  // the seller signs for the NFT leaving their token account
+  transfer_nft(
+    &mint,
+    mint_account_info,
+    target_account_info,
+    seller_token_account_info,
+    buyer_token_account_info,
+    token_program_info,
+  )?;
+
+  // non Royalty transaction transfer full amount to target
+  invoke(
+    &system_instruction::transfer(
+      payer_account_info.key,
+      target_account_info.key,
+      data.amount
+    ),
+    &[
+      payer_account_info.clone(),
+      target_account_info.clone(),
+      system_info.clone()
+    ]
+  )?;
+
+  Ok(())
+}
+
+/// the accounts of CreateMetaData, read and checked the same way for every kind
+pub struct CreateAccounts<'a, 'b> {
+  pub metadata: &'a AccountInfo<'b>,
+  pub mint: &'a AccountInfo<'b>,
+  pub owner: &'a AccountInfo<'b>, // royalty owner of an ArtNFT, game authority of a GameItem
+  pub mint_authority: &'a AccountInfo<'b>,
+  pub new_mint_authority: &'a AccountInfo<'b>,
+  pub payer: &'a AccountInfo<'b>,
+  pub update_authority: Option<&'a AccountInfo<'b>>, // unless the update type is None
+  pub system: &'a AccountInfo<'b>,
+  pub rent: &'a AccountInfo<'b>,
+  pub token_program: &'a AccountInfo<'b>,
+  pub collection: Option<&'a AccountInfo<'b>>, // when in collection
+}
+
+impl<'a, 'b> CreateAccounts<'a, 'b> {
+  pub fn from_accounts(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'b>],
+    args: &MetadataArgs,
+  ) -> Result<Self, ProgramError> {
+    msg!("Get accounts");
+    let account_iter = &mut accounts.iter();
+    let metadata = next_account_info(account_iter)?;
+    let mint = next_account_info(account_iter)?;
+    let owner = next_account_info(account_iter)?;
+    let mint_authority = next_account_info(account_iter)?;
+    let new_mint_authority = next_account_info(account_iter)?;
+    let payer = next_account_info(account_iter)?;
+    let update_authority = match args.update_type {
+      UpdateType::None => None,
+      _ => Some(next_account_info(account_iter)?),
+    };
+    let system = next_account_info(account_iter)?;
+    let rent = next_account_info(account_iter)?;
+    let token_program = next_account_info(account_iter)?;
+    let collection = match args.in_collection {
+      false => None,
+      true => Some(next_account_info(account_iter)?),
+    };
+
+    if let (UpdateType::Multisig, Some(multisig_account_info)) = (args.update_type, update_authority) {
+      msg!("verify multisig update authority");
+      assert_owned_by(multisig_account_info, program_id)?;
+      if !MultisigConfig::from_account_info(multisig_account_info)?.is_initialized {
+        return Err(ReeMetaError::Uninitialized.into())
+      }
+    }
+
+    msg!("verify system accounts");
+    if *system.key != system_program::ID || *rent.key != RENT_ID {
+      msg!("invalid system accounts");
+      return Err(ReeMetaError::InvalidInstruction.into())
+    }
+    assert_token_program(token_program)?;
+
+    msg!("assert mint is a token program mint");
+    let mint_state: Mint = unpack_mint(mint, token_program.key)?;
+    assert_valid_mint_authority(&mint_state.mint_authority, mint_authority)?;
+
+    Ok(CreateAccounts{
+      metadata,
+      mint,
+      owner,
+      mint_authority,
+      new_mint_authority,
+      payer,
+      update_authority,
+      system,
+      rent,
+      token_program,
+      collection,
+    })
+  }
+
+  /// the metadata of the kind in args holding data
+  pub fn new_metadata<T: NftKind>(&self, args: &MetadataArgs, data: T) -> Metadata<T> {
+    Metadata{
+      kind: args.kind,
+      mint: *self.mint.key,
+      data,
+      is_modifiable: args.is_modifiable,
+      update_type: args.update_type,
+      collection: self.collection.map(|account_info| *account_info.key),
+      update_authority: self.update_authority.map(|account_info| *account_info.key),
+    }
+  }
+
+  /// allocate the metadata PDA, hand the mint authority over and write the metadata
+  pub fn create_metadata<T: NftKind>(&self, program_id: &Pubkey, metadata: &Metadata<T>) -> ProgramResult {
+    let metadata_bump_seed = assert_metadata_address(program_id, self.metadata, self.mint.key)?;
+    let metadata_authority_seeds = &[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      self.mint.key.as_ref(),
+      &[metadata_bump_seed]
+    ];
+
+    msg!("allocate and assign");
+    let rent = &Rent::from_account_info(self.rent)?;
+    create_pda_account(
+      self.payer,
+      self.metadata,
+      self.system,
+      program_id,
+      rent,
+      metadata.size(),
+      metadata_authority_seeds,
+    )?;
+
+    if *self.mint_authority.key != *self.new_mint_authority.key {
+      invoke(
+        &spl_token_2022::instruction::set_authority(
+          self.token_program.key,
+          self.mint.key,
+          Some(self.new_mint_authority.key),
+          spl_token_2022::instruction::AuthorityType::MintTokens,
+          self.mint_authority.key,
+          &[self.mint_authority.key]
+        )?,
+        &[
+          self.mint.clone(),
+          self.mint_authority.clone(),
+        ]
+      )?;
+    }
+
+    msg!("write data to account");
+    metadata.serialize(&mut *self.metadata.data.borrow_mut())?;
+    Ok(())
+  }
+}
+
+/// an NftKind behind a reference the processor can pick by Kind at runtime. each call
+/// deserializes the metadata in the first account as the kind's data, except create
+/// which makes the metadata account
+pub trait KindHandler {
+  fn kind(&self) -> Kind;
+  fn create(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: CreateMetadataArgs) -> ProgramResult;
+  fn lock_nft(&self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult;
+  fn add_royalty(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: AddRoyaltyArgs) -> ProgramResult;
+  fn set_primary_split(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: SetPrimarySplitArgs) -> ProgramResult;
+  fn create_royalty_vault(&self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult;
+  fn quote_royalty(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: QuoteRoyaltyArgs) -> ProgramResult;
+  fn nft_transaction(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: NftTransactionArgs) -> ProgramResult;
+  fn transfer(&self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult;
+  fn set_attribute(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: SetAttributeArgs) -> ProgramResult;
+  fn remove_attribute(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: RemoveAttributeArgs) -> ProgramResult;
+  fn update_uri(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: UpdateUriArgs) -> ProgramResult;
+  fn update_stats(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: UpdateStatsArgs) -> ProgramResult;
+  fn revoke(&self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult;
+  fn fractionalize(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: FractionalizeArgs) -> ProgramResult;
+}
+
+pub struct Registered<T: NftKind>(PhantomData<T>);
+
+impl<T: NftKind> Registered<T> {
+  fn metadata(accounts: &[AccountInfo]) -> Result<Metadata<T>, ProgramError> {
+    let metadata_account_info = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+    Metadata::<T>::from_account_info(metadata_account_info)
+  }
+}
+
+impl<T: NftKind> KindHandler for Registered<T> {
+  fn kind(&self) -> Kind {
+    T::KIND
+  }
+
+  fn create(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: CreateMetadataArgs) -> ProgramResult {
+    T::create(program_id, accounts, data)
+  }
+
+  fn lock_nft(&self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    T::lock_nft(program_id, accounts, Self::metadata(accounts)?)
+  }
+
+  fn add_royalty(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: AddRoyaltyArgs) -> ProgramResult {
+    T::add_royalty(program_id, accounts, Self::metadata(accounts)?, data)
+  }
+
+  fn set_primary_split(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: SetPrimarySplitArgs) -> ProgramResult {
+    T::set_primary_split(program_id, accounts, Self::metadata(accounts)?, data)
+  }
+
+  fn create_royalty_vault(&self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    T::create_royalty_vault(program_id, accounts, Self::metadata(accounts)?)
+  }
+
+  fn quote_royalty(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: QuoteRoyaltyArgs) -> ProgramResult {
+    T::quote_royalty(program_id, accounts, Self::metadata(accounts)?, data)
+  }
+
+  fn nft_transaction(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: NftTransactionArgs) -> ProgramResult {
+    T::nft_transaction(program_id, accounts, Self::metadata(accounts)?, data)
+  }
+
+  fn transfer(&self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    T::transfer(program_id, accounts, Self::metadata(accounts)?)
+  }
+
+  fn set_attribute(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: SetAttributeArgs) -> ProgramResult {
+    T::set_attribute(program_id, accounts, Self::metadata(accounts)?, data)
+  }
+
+  fn remove_attribute(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: RemoveAttributeArgs) -> ProgramResult {
+    T::remove_attribute(program_id, accounts, Self::metadata(accounts)?, data)
+  }
+
+  fn update_uri(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: UpdateUriArgs) -> ProgramResult {
+    T::update_uri(program_id, accounts, Self::metadata(accounts)?, data)
+  }
+
+  fn update_stats(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: UpdateStatsArgs) -> ProgramResult {
+    T::update_stats(program_id, accounts, Self::metadata(accounts)?, data)
+  }
+
+  fn revoke(&self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    T::revoke(program_id, accounts, Self::metadata(accounts)?)
+  }
+
+  fn fractionalize(&self, program_id: &Pubkey, accounts: &[AccountInfo], data: FractionalizeArgs) -> ProgramResult {
+    T::fractionalize(program_id, accounts, Self::metadata(accounts)?, data)
+  }
+}
+
+/// every NFT kind the program handles, a new kind is registered by adding it here
+const REGISTRY: &[&dyn KindHandler] = &[
+  &Registered::<ArtNft>(PhantomData),
+  &Registered::<CustomNft>(PhantomData),
+  &Registered::<GameItem>(PhantomData),
+];
+
+/// the handler registered for kind
+pub fn handler_for(kind: Kind) -> Result<&'static dyn KindHandler, ProgramError> {
+  match REGISTRY.iter().find(|handler| handler.kind() == kind) {
+    None => {
+      msg!("no handler registered for this NFT Kind");
+      Err(ReeMetaError::InvalidNFTKind.into())
+    },
+    Some(handler) => Ok(*handler),
+  }
+}
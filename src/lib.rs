@@ -11,7 +11,17 @@ pub mod state;
 pub mod utils;
 pub mod artNft;
 pub mod customNft;
+pub mod gameItem;
 pub mod unpack;
+pub mod metaplex;
+pub mod pda;
+pub mod accounts;
+pub mod decode;
+pub mod quote;
+pub mod cpi;
+pub mod kind;
+#[cfg(feature = "serde")]
+pub mod serde_pubkey;
 
 #[cfg(not(feature = "no-entrypoint"))]
 pub mod entrypoint;
@@ -0,0 +1,285 @@
+use crate::{
+  error::ReeMetaError,
+  state::{
+    Metadata,
+    ArtNft,
+    Royalty,
+    TOTAL_SHARES,
+  },
+  utils::json_string,
+};
+use borsh::BorshDeserialize;
+
+use solana_program::{
+  borsh::try_from_slice_unchecked,
+  account_info::AccountInfo,
+  program_error::ProgramError,
+  pubkey::Pubkey,
+  msg,
+};
+
+// Metaplex Token Metadata program
+solana_program::declare_id!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+pub const METAPLEX_PREFIX: &str = "metadata";
+pub const METAPLEX_METADATA_KEY: u8 = 4; // Key::MetadataV1
+
+// only the leading fields of the Metaplex account are read, the rest is ignored
+#[repr(C)]
+#[derive(BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct MetaplexCreator {
+  pub address: Pubkey,
+  pub verified: bool,
+  pub share: u8,
+}
+
+#[repr(C)]
+#[derive(BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct MetaplexData {
+  pub name: String,
+  pub symbol: String,
+  pub uri: String,
+  pub seller_fee_basis_points: u16,
+  pub creators: Option<Vec<MetaplexCreator>>,
+}
+
+#[repr(C)]
+#[derive(BorshDeserialize, PartialEq, Debug, Clone)]
+pub struct MetaplexMetadata {
+  pub key: u8,
+  pub update_authority: Pubkey,
+  pub mint: Pubkey,
+  pub data: MetaplexData,
+  pub primary_sale_happened: bool,
+  pub is_mutable: bool,
+}
+
+impl MetaplexMetadata {
+  pub fn from_account_info(account_info: &AccountInfo) -> Result<MetaplexMetadata, ProgramError> {
+    if *account_info.owner != id() {
+      msg!("metadata is not owned by the Metaplex Token Metadata program");
+      return Err(ReeMetaError::IncorrectOwner.into())
+    }
+
+    let data = &account_info.data.borrow();
+    let md: MetaplexMetadata = try_from_slice_unchecked(data)
+      .map_err(|_| ReeMetaError::InvalidMetaplexMetadata)?;
+
+    if md.key != METAPLEX_METADATA_KEY {
+      return Err(ReeMetaError::InvalidMetaplexMetadata.into())
+    }
+    Ok(md)
+  }
+
+  pub fn find_program_address(mint_id: &Pubkey) -> (Pubkey, u8) {
+    let seeds = &[
+      METAPLEX_PREFIX.as_bytes(),
+      ID.as_ref(),
+      mint_id.as_ref(),
+    ];
+
+    return Pubkey::find_program_address(seeds, &ID);
+  }
+}
+
+/// Metaplex pads its strings with null bytes
+fn trim_padding(value: &str) -> String {
+  value.trim_end_matches(char::from(0)).to_string()
+}
+
+/// map a Metaplex metadata account onto an ArtNft. creators become the royalties,
+/// or the update authority holds every share when there are none. the seller fee is
+/// in basis points and the resale fee is a whole percentage so it rounds down.
+pub fn art_nft_from_metaplex(md: &MetaplexMetadata) -> ArtNft {
+  let mut royalties: Vec<Royalty> = match &md.data.creators {
+    None => Vec::new(),
+    Some(creators) => creators.iter()
+      .filter(|creator| creator.share > 0)
+      .map(|creator| Royalty{
+        address: creator.address,
+        share: creator.share as u16,
+        verified: creator.verified,
+      })
+      .collect(),
+  };
+
+  if royalties.len() == 0 {
+    royalties.push(Royalty{
+      address: md.update_authority,
+      share: TOTAL_SHARES,
+      verified: true,
+    });
+  }
+
+  if md.data.seller_fee_basis_points % 100 != 0 {
+    msg!("seller fee of {} basis points rounded down to a whole percent", md.data.seller_fee_basis_points);
+  }
+
+  ArtNft{
+    name: trim_padding(&md.data.name),
+    symbol: trim_padding(&md.data.symbol),
+    uri: trim_padding(&md.data.uri),
+    resale_fee: md.data.seller_fee_basis_points / 100,
+    initial_sale: md.primary_sale_happened,
+    collection: None,
+    royalties,
+    royalty_vault: false,
+    primary_split: Vec::new(),
+    enforced: false,
+    attributes: Vec::new(),
+    soulbound: false,
+  }
+}
+
+/// render the ArtNft metadata in the shape of a Metaplex metadata account for wallets
+pub fn to_metaplex_json(metadata: &Metadata<ArtNft>) -> String {
+  let creators: Vec<String> = metadata.data.royalties.iter().map(|royalty| format!(
+    "{{\"address\":{},\"verified\":{},\"share\":{}}}",
+    json_string(&royalty.address.to_string()),
+    royalty.verified,
+    royalty.share,
+  )).collect();
+
+  let attributes: Vec<String> = metadata.data.attributes.iter().map(|(key, value)| format!(
+    "{{\"trait_type\":{},\"value\":{}}}",
+    json_string(key),
+    json_string(value),
+  )).collect();
+
+  let update_authority = match metadata.update_authority {
+    None => "null".to_string(),
+    Some(key) => json_string(&key.to_string()),
+  };
+
+  let collection = match metadata.collection {
+    None => "null".to_string(),
+    Some(key) => format!("{{\"verified\":false,\"key\":{}}}", json_string(&key.to_string())),
+  };
+
+  format!(
+    "{{\"update_authority\":{},\"mint\":{},\"data\":{{\"name\":{},\"symbol\":{},\"uri\":{},\"seller_fee_basis_points\":{},\"creators\":[{}]}},\"primary_sale_happened\":{},\"is_mutable\":{},\"collection\":{},\"attributes\":[{}]}}",
+    update_authority,
+    json_string(&metadata.mint.to_string()),
+    json_string(&metadata.data.name),
+    json_string(&metadata.data.symbol),
+    json_string(&metadata.data.uri),
+    metadata.data.resale_fee as u32 * 100,
+    creators.join(","),
+    metadata.data.initial_sale,
+    metadata.is_modifiable,
+    collection,
+    attributes.join(","),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use borsh::BorshSerialize;
+  use assert_matches::assert_matches;
+
+  fn creator(share: u8, verified: bool) -> MetaplexCreator {
+    MetaplexCreator{ address: Pubkey::new_unique(), verified, share }
+  }
+
+  fn metaplex(seller_fee_basis_points: u16, creators: Option<Vec<MetaplexCreator>>) -> MetaplexMetadata {
+    MetaplexMetadata{
+      key: METAPLEX_METADATA_KEY,
+      update_authority: Pubkey::new_unique(),
+      mint: Pubkey::new_unique(),
+      data: MetaplexData{
+        name: format!("art{}", "\0".repeat(29)),
+        symbol: format!("ART{}", "\0".repeat(7)),
+        uri: format!("https://example.com/art.json{}", "\0".repeat(172)),
+        seller_fee_basis_points,
+        creators,
+      },
+      primary_sale_happened: true,
+      is_mutable: true,
+    }
+  }
+
+  // the account layout of the Metaplex program, MetaplexMetadata only reads it
+  fn account_data(md: &MetaplexMetadata) -> Vec<u8> {
+    let creators: Option<Vec<(Pubkey, bool, u8)>> = md.data.creators.as_ref().map(|creators| {
+      creators.iter().map(|creator| (creator.address, creator.verified, creator.share)).collect()
+    });
+    (
+      md.key,
+      md.update_authority,
+      md.mint,
+      (&md.data.name, &md.data.symbol, &md.data.uri, md.data.seller_fee_basis_points, creators),
+      md.primary_sale_happened,
+      md.is_mutable,
+    ).try_to_vec().unwrap()
+  }
+
+  fn from_account(data: &mut [u8], owner: &Pubkey) -> Result<MetaplexMetadata, ProgramError> {
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    let account_info = AccountInfo::new(&key, false, false, &mut lamports, data, owner, false, 0);
+    MetaplexMetadata::from_account_info(&account_info)
+  }
+
+  #[test]
+  fn test_trims_null_padding() {
+    let art = art_nft_from_metaplex(&metaplex(500, None));
+    assert_eq!(art.name, "art");
+    assert_eq!(art.symbol, "ART");
+    assert_eq!(art.uri, "https://example.com/art.json");
+  }
+
+  #[test]
+  fn test_seller_fee_rounds_down() {
+    for (basis_points, resale_fee) in [(250, 2), (1_000, 10), (99, 0), (10_000, 100)] {
+      assert_eq!(art_nft_from_metaplex(&metaplex(basis_points, None)).resale_fee, resale_fee);
+    }
+  }
+
+  #[test]
+  fn test_creators_become_royalties() {
+    let creators = vec![creator(60, true), creator(0, true), creator(40, false)];
+    let art = art_nft_from_metaplex(&metaplex(500, Some(creators.clone())));
+
+    assert_eq!(art.royalties, vec![
+      Royalty{ address: creators[0].address, share: 60, verified: true },
+      Royalty{ address: creators[2].address, share: 40, verified: false },
+    ]);
+    assert!(art.initial_sale);
+    assert_matches!(art.validate(), Ok(()));
+  }
+
+  #[test]
+  fn test_update_authority_without_creators() {
+    for creators in [None, Some(Vec::new()), Some(vec![creator(0, true), creator(0, false)])] {
+      let md = metaplex(500, creators);
+      assert_eq!(art_nft_from_metaplex(&md).royalties, vec![
+        Royalty{ address: md.update_authority, share: TOTAL_SHARES, verified: true },
+      ]);
+    }
+  }
+
+  #[test]
+  fn test_from_account_info() {
+    let md = metaplex(500, Some(vec![creator(100, true)]));
+    assert_eq!(from_account(&mut account_data(&md), &ID), Ok(md));
+  }
+
+  #[test]
+  fn test_wrong_key_byte() {
+    let md = MetaplexMetadata{ key: METAPLEX_METADATA_KEY + 1, ..metaplex(500, None) };
+    assert_eq!(
+      from_account(&mut account_data(&md), &ID),
+      Err(ReeMetaError::InvalidMetaplexMetadata.into())
+    );
+  }
+
+  #[test]
+  fn test_wrong_owner() {
+    let md = metaplex(500, None);
+    assert_eq!(
+      from_account(&mut account_data(&md), &Pubkey::new_unique()),
+      Err(ReeMetaError::IncorrectOwner.into())
+    );
+  }
+}
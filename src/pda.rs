@@ -0,0 +1,109 @@
+use crate::state::{
+  PREFIX,
+  VAULT_PREFIX,
+  FREEZE_PREFIX,
+  MULTISIG_PREFIX,
+  DELEGATE_PREFIX,
+  CONFIG_PREFIX,
+  DISTRIBUTOR_PREFIX,
+  STAKE_PREFIX,
+};
+
+use solana_program::pubkey::Pubkey;
+
+/// metadata account of a mint
+pub fn find_metadata_address(program_id: &Pubkey, mint_id: &Pubkey) -> (Pubkey, u8) {
+  let seeds = &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    mint_id.as_ref(),
+  ];
+
+  return Pubkey::find_program_address(seeds, program_id);
+}
+
+/// royalty vault holding the unclaimed royalties of a mint
+pub fn find_royalty_vault_address(program_id: &Pubkey, mint_id: &Pubkey) -> (Pubkey, u8) {
+  let seeds = &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    mint_id.as_ref(),
+    VAULT_PREFIX.as_bytes(),
+  ];
+
+  return Pubkey::find_program_address(seeds, program_id);
+}
+
+/// PDA holding the freeze authority of mints with enforced royalties
+pub fn find_freeze_authority_address(program_id: &Pubkey, mint_id: &Pubkey) -> (Pubkey, u8) {
+  let seeds = &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    mint_id.as_ref(),
+    FREEZE_PREFIX.as_bytes(),
+  ];
+
+  return Pubkey::find_program_address(seeds, program_id);
+}
+
+/// multisig config derived from the key that created it
+pub fn find_multisig_address(program_id: &Pubkey, base: &Pubkey) -> (Pubkey, u8) {
+  let seeds = &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    MULTISIG_PREFIX.as_bytes(),
+    base.as_ref(),
+  ];
+
+  return Pubkey::find_program_address(seeds, program_id);
+}
+
+/// delegate record of a delegate on one metadata account
+pub fn find_delegate_record_address(program_id: &Pubkey, metadata: &Pubkey, delegate: &Pubkey) -> (Pubkey, u8) {
+  let seeds = &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    metadata.as_ref(),
+    DELEGATE_PREFIX.as_bytes(),
+    delegate.as_ref(),
+  ];
+
+  return Pubkey::find_program_address(seeds, program_id);
+}
+
+/// the program config, one per program
+pub fn find_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+  let seeds = &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    CONFIG_PREFIX.as_bytes(),
+  ];
+
+  return Pubkey::find_program_address(seeds, program_id);
+}
+
+/// distributor of a fractionalized royalty share, one per holder and mint
+pub fn find_distributor_address(program_id: &Pubkey, mint_id: &Pubkey, holder: &Pubkey) -> (Pubkey, u8) {
+  let seeds = &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    mint_id.as_ref(),
+    DISTRIBUTOR_PREFIX.as_bytes(),
+    holder.as_ref(),
+  ];
+
+  return Pubkey::find_program_address(seeds, program_id);
+}
+
+/// fractions staked by an owner in a distributor
+pub fn find_fraction_stake_address(program_id: &Pubkey, distributor: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+  let seeds = &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    distributor.as_ref(),
+    STAKE_PREFIX.as_bytes(),
+    owner.as_ref(),
+  ];
+
+  return Pubkey::find_program_address(seeds, program_id);
+}
@@ -1,42 +1,83 @@
 use crate::{
   error::ReeMetaError,
   instruction::{
-    MetadataArgs,
-    MetadataArgsRRA,
+    CreateMetadataArgs,
     ReeMetadataInstruction,
     AddRoyaltyArgs, NftTransactionArgs,
+    SetPrimarySplitArgs,
+    SetAttributeArgs,
+    RemoveAttributeArgs,
+    InitMultisigArgs,
+    ApproveDelegateArgs,
+    UpdateUriArgs,
+    ConfigArgs,
+    SetPausedArgs,
+    QuoteRoyaltyArgs,
+    UpdateStatsArgs,
+    FractionalizeArgs,
+    FractionAmountArgs,
   },
   state::{
     Metadata,
     ArtNft,
-    CustomNft,
-    Royalty,
+    RoyaltyVault,
+    MultisigConfig,
+    DelegateRecord,
+    ProgramConfig,
+    FractionDistributor,
+    FractionStake,
+    MULTISIG_PREFIX,
+    DELEGATE_PREFIX,
+    CONFIG_PREFIX,
+    DISTRIBUTOR_PREFIX,
+    STAKE_PREFIX,
+    SCOPE_ALL,
     PREFIX,
+    FREEZE_PREFIX,
     Kind,
     UpdateType
   },
   utils::{
-    assert_initialized,
     assert_valid_mint_authority,
     assert_owned_by,
+    assert_metadata_account,
+    assert_metadata_address,
+    assert_token_program,
+    assert_update_authority,
+    assert_upgrade_authority,
+    create_pda_account,
+    load_program_config,
+    unpack_mint,
+    unpack_token_account,
   },
-  artNft,
-  customNft
+  metaplex::{MetaplexMetadata, art_nft_from_metaplex},
+  pda::{
+    find_royalty_vault_address,
+    find_freeze_authority_address,
+    find_multisig_address,
+    find_delegate_record_address,
+    find_config_address,
+    find_distributor_address,
+    find_fraction_stake_address,
+  },
+  kind::handler_for,
 };
 use borsh::BorshSerialize;
 
 use solana_program::{
   account_info::{next_account_info, AccountInfo},
+  clock::Clock,
   entrypoint::ProgramResult,
   msg,
   program::{invoke, invoke_signed},
+  program_error::ProgramError,
+  program_option::COption,
   pubkey::Pubkey,
-  system_instruction,
   sysvar::{rent::{Rent, ID as RENT_ID}, Sysvar},
   system_program, 
 };
 
-use spl_token::{
+use spl_token_2022::{
   state::{Mint, Account as TokenAccount}
 };
 
@@ -49,15 +90,15 @@ impl Processor {
   ) -> ProgramResult {
     msg!("REEMETA Instruction");
     let instruction = ReeMetadataInstruction::unpack(input)?;
+    instruction.validate_accounts(accounts)?;
+    if instruction.is_pausable() && load_program_config(program_id, accounts)?.paused {
+      msg!("program is paused");
+      return Err(ReeMetaError::ProgramPaused.into())
+    }
     match instruction {
       ReeMetadataInstruction::CreateMetaData(args) => {
         msg!("Create Metadata Account");
-        process_create_metadata(
-          program_id,
-          accounts,
-          args.metadata,
-          args.aar_data,
-        )
+        process_create_metadata(program_id, accounts, args)
       },
       ReeMetadataInstruction::MintNFT() => {
         process_mint_nft(program_id, accounts)
@@ -70,6 +111,72 @@ impl Processor {
       },
       ReeMetadataInstruction::NftTransaction(args) => {
         process_nft_transaction(program_id, accounts, args)
+      },
+      ReeMetadataInstruction::CreateRoyaltyVault() => {
+        process_create_royalty_vault(program_id, accounts)
+      },
+      ReeMetadataInstruction::ClaimRoyalties() => {
+        process_claim_royalties(program_id, accounts)
+      },
+      ReeMetadataInstruction::SetPrimarySplit(args) => {
+        process_set_primary_split(program_id, accounts, args)
+      },
+      ReeMetadataInstruction::Transfer() => {
+        process_transfer(program_id, accounts)
+      },
+      ReeMetadataInstruction::Buy(args) => {
+        process_nft_transaction(program_id, accounts, args)
+      },
+      ReeMetadataInstruction::ImportFromMetaplex() => {
+        process_import_from_metaplex(program_id, accounts)
+      },
+      ReeMetadataInstruction::SetAttribute(args) => {
+        process_set_attribute(program_id, accounts, args)
+      },
+      ReeMetadataInstruction::RemoveAttribute(args) => {
+        process_remove_attribute(program_id, accounts, args)
+      },
+      ReeMetadataInstruction::InitMultisig(args) => {
+        process_init_multisig(program_id, accounts, args)
+      },
+      ReeMetadataInstruction::ApproveDelegate(args) => {
+        process_approve_delegate(program_id, accounts, args)
+      },
+      ReeMetadataInstruction::RevokeDelegate() => {
+        process_revoke_delegate(program_id, accounts)
+      },
+      ReeMetadataInstruction::UpdateUri(args) => {
+        process_update_uri(program_id, accounts, args)
+      },
+      ReeMetadataInstruction::InitConfig(args) => {
+        process_init_config(program_id, accounts, args)
+      },
+      ReeMetadataInstruction::UpdateConfig(args) => {
+        process_update_config(program_id, accounts, args)
+      },
+      ReeMetadataInstruction::SetPaused(args) => {
+        process_set_paused(program_id, accounts, args)
+      },
+      ReeMetadataInstruction::QuoteRoyalty(args) => {
+        process_quote_royalty(program_id, accounts, args)
+      },
+      ReeMetadataInstruction::UpdateStats(args) => {
+        process_update_stats(program_id, accounts, args)
+      },
+      ReeMetadataInstruction::Revoke() => {
+        process_revoke(program_id, accounts)
+      },
+      ReeMetadataInstruction::Fractionalize(args) => {
+        process_fractionalize(program_id, accounts, args)
+      },
+      ReeMetadataInstruction::DepositFractions(args) => {
+        process_deposit_fractions(program_id, accounts, args)
+      },
+      ReeMetadataInstruction::WithdrawFractions(args) => {
+        process_withdraw_fractions(program_id, accounts, args)
+      },
+      ReeMetadataInstruction::ClaimFractionalRoyalties() => {
+        process_claim_fractional_royalties(program_id, accounts)
       }
     }
   }
@@ -78,67 +185,264 @@ impl Processor {
 pub fn process_create_metadata (
   program_id: &Pubkey,
   accounts: &[AccountInfo],
-  metadata_data: MetadataArgs,
-  aar_data: MetadataArgsRRA,
+  data: CreateMetadataArgs,
+) -> ProgramResult {
+  handler_for(data.metadata.kind)?.create(program_id, accounts, data)
+}
+
+pub fn process_mint_nft(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
 ) -> ProgramResult {
-  msg!("Get accounts");
   let account_iter = &mut accounts.iter();
-  let metadata_acount_info = next_account_info(account_iter)?;
   let mint_account_info = next_account_info(account_iter)?;
-  let royalty_owner_account_info = next_account_info(account_iter)?;
-  let mint_authority_account_info = next_account_info(account_iter)?;
-  let new_mint_authority_account_info = next_account_info(account_iter)?;
-  let payer_account_info = next_account_info(account_iter)?;
-  let update_authority: Option<&AccountInfo> = match metadata_data.update_type {
-    UpdateType::None => {
-      None
-    },
-    _ => {
-      Some(next_account_info(account_iter)?)
+  let authority_account_info = next_account_info(account_iter)?;
+  let recipient_token_account_info = next_account_info(account_iter)?;
+  let token_program_info = next_account_info(account_iter)?;
+
+  // the mint can belong to either token program, CPIs go to whichever owns it
+  assert_token_program(token_program_info)?;
+
+  let mint: Mint = unpack_mint(mint_account_info, token_program_info.key)?;
+
+  let recipient_token_account: TokenAccount = unpack_token_account(recipient_token_account_info, token_program_info.key)?;
+
+  assert_valid_mint_authority(&mint.mint_authority, &authority_account_info)?;
+
+  if recipient_token_account.mint != *mint_account_info.key {
+    return Err(ReeMetaError::InvalidInstruction.into())
+  }
+
+  // mint the token then remove the mint authority from the mint.
+  invoke(
+    &spl_token_2022::instruction::mint_to(
+      token_program_info.key, 
+      mint_account_info.key, 
+      recipient_token_account_info.key, 
+      authority_account_info.key, 
+      &[authority_account_info.key], 
+      1
+    )?,
+    &[
+      mint_account_info.clone(),
+      recipient_token_account_info.clone(),
+      authority_account_info.clone(),
+    ]
+  )?;
+
+  // enforced NFTs stay frozen so they can only move through ree-meta
+  let (freeze_key, freeze_bump_seed) = find_freeze_authority_address(program_id, mint_account_info.key);
+  if mint.freeze_authority == COption::Some(freeze_key) {
+    let freeze_authority_account_info = next_account_info(account_iter)?;
+    if *freeze_authority_account_info.key != freeze_key {
+      return Err(ReeMetaError::InvalidFreezeAuthority.into())
     }
-  };
+
+    invoke_signed(
+      &spl_token_2022::instruction::freeze_account(
+        token_program_info.key,
+        recipient_token_account_info.key,
+        mint_account_info.key,
+        &freeze_key,
+        &[]
+      )?,
+      &[
+        recipient_token_account_info.clone(),
+        mint_account_info.clone(),
+        freeze_authority_account_info.clone(),
+      ],
+      &[&[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        mint_account_info.key.as_ref(),
+        FREEZE_PREFIX.as_bytes(),
+        &[freeze_bump_seed]
+      ]]
+    )?;
+  }
+
+  invoke(
+    &spl_token_2022::instruction::set_authority(
+      token_program_info.key, 
+      mint_account_info.key, 
+      None, 
+      spl_token_2022::instruction::AuthorityType::MintTokens, 
+      authority_account_info.key, 
+      &[authority_account_info.key]
+    )?,
+    &[
+      mint_account_info.clone(),
+      authority_account_info.clone(),
+    ]
+  )?;
+
+  Ok(())
+}
+
+pub fn process_lock_nft (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+
+  let header = assert_metadata_account(program_id, metadata_account_info)?;
+
+  handler_for(header.kind)?.lock_nft(program_id, accounts)
+}
+
+pub fn process_add_royalty (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: AddRoyaltyArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+
+  let header = assert_metadata_account(program_id, metadata_account_info)?;
+
+  handler_for(header.kind)?.add_royalty(program_id, accounts, data)
+}
+
+pub fn process_nft_transaction (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: NftTransactionArgs
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+
+  let header = assert_metadata_account(program_id, metadata_account_info)?;
+
+  handler_for(header.kind)?.nft_transaction(program_id, accounts, data)
+}
+
+pub fn process_create_royalty_vault (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+
+  let header = assert_metadata_account(program_id, metadata_account_info)?;
+
+  handler_for(header.kind)?.create_royalty_vault(program_id, accounts)
+}
+
+pub fn process_claim_royalties (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let vault_account_info = next_account_info(account_iter)?;
+  let holder_account_info = next_account_info(account_iter)?;
+
+  assert_owned_by(vault_account_info, program_id)?;
+
+  if !holder_account_info.is_signer {
+    msg!("royalty holder must sign");
+    return Err(ReeMetaError::InvalidInstruction.into())
+  }
+
+  let mut vault = RoyaltyVault::from_account_info(vault_account_info)?;
+  if !vault.is_initialized {
+    return Err(ReeMetaError::Uninitialized.into())
+  }
+
+  let (vault_key, _) = find_royalty_vault_address(program_id, &vault.mint);
+  if vault_key != *vault_account_info.key {
+    return Err(ReeMetaError::InvalidVault.into())
+  }
+
+  let balance = match vault.balances.iter_mut()
+    .find(|b| b.address == *holder_account_info.key) {
+      None => return Err(ReeMetaError::NothingToClaim.into()),
+      Some(balance) => balance,
+    };
+
+  let amount = balance.amount;
+  if amount == 0 {
+    return Err(ReeMetaError::NothingToClaim.into())
+  }
+  balance.amount = 0;
+
+  msg!("claiming {} lamports for {}", amount, holder_account_info.key.to_string());
+
+  // the vault is owned by this program so lamports can be moved directly
+  let vault_lamports = vault_account_info.lamports()
+    .checked_sub(amount)
+    .ok_or(ReeMetaError::MathOverflow)?;
+  **vault_account_info.try_borrow_mut_lamports()? = vault_lamports;
+  **holder_account_info.try_borrow_mut_lamports()? += amount;
+
+  vault.serialize(&mut *vault_account_info.data.borrow_mut())?;
+
+  Ok(())
+}
+
+pub fn process_set_primary_split (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: SetPrimarySplitArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+
+  let header = assert_metadata_account(program_id, metadata_account_info)?;
+
+  handler_for(header.kind)?.set_primary_split(program_id, accounts, data)
+}
+
+pub fn process_transfer (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+
+  let header = assert_metadata_account(program_id, metadata_account_info)?;
+
+  handler_for(header.kind)?.transfer(program_id, accounts)
+}
+
+pub fn process_import_from_metaplex (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+  let mint_account_info = next_account_info(account_iter)?;
+  let metaplex_account_info = next_account_info(account_iter)?;
+  let payer_account_info = next_account_info(account_iter)?;
+  let update_authority_account_info = next_account_info(account_iter)?;
   let system_info = next_account_info(account_iter)?;
   let rent_info = next_account_info(account_iter)?;
-  let token_info = next_account_info(account_iter)?;
 
-  msg!("verify system accounts");
-  if *system_info.key != system_program::ID
-    || *rent_info.key != RENT_ID
-    || *token_info.key != spl_token::ID
-  {
+  if *system_info.key != system_program::ID || *rent_info.key != RENT_ID {
     msg!("invalid system accounts");
     return Err(ReeMetaError::InvalidInstruction.into())
   }
 
-  msg!("assert mint is a token program mint");
-  assert_owned_by(mint_account_info, token_info.key)?;
+  let (metaplex_key, _) = MetaplexMetadata::find_program_address(mint_account_info.key);
+  if metaplex_key != *metaplex_account_info.key {
+    msg!("Invalid Metaplex PDA");
+    return Err(ReeMetaError::InvalidMetaplexMetadata.into())
+  }
 
-  let genesis_royalty = Royalty{
-    address: *royalty_owner_account_info.key,
-    share: 100, 
-    verified: true
-  };
+  let metaplex = MetaplexMetadata::from_account_info(metaplex_account_info)?;
+  if metaplex.mint != *mint_account_info.key {
+    return Err(ReeMetaError::InvalidMetaplexMetadata.into())
+  }
 
-  let mut royalties: Vec<Royalty> = Vec::<Royalty>::new();
-  royalties.push(genesis_royalty);
-    
-  let art_nft: ArtNft = ArtNft{
-    name: aar_data.name,
-    symbol: aar_data.symbol,
-    uri: aar_data.uri,
-    resale_fee: aar_data.resale_fee,
-    initial_sale: false,
-    collection: None,
-    royalties: royalties,
-  };
+  // only the Metaplex update authority can claim the NFT here
+  if !update_authority_account_info.is_signer || metaplex.update_authority != *update_authority_account_info.key {
+    return Err(ReeMetaError::InvalidUpdateAuthority.into())
+  }
 
-  let metadata_seeds = &[
-    PREFIX.as_bytes(),
-    program_id.as_ref(),
-    mint_account_info.key.as_ref()
-  ];
+  let art_nft = art_nft_from_metaplex(&metaplex);
+  art_nft.validate()?;
 
-  let (metadata_key, metadata_bump_seed) = Pubkey::find_program_address(metadata_seeds, program_id);
+  let metadata_bump_seed = assert_metadata_address(program_id, metadata_account_info, mint_account_info.key)?;
 
   let metadata_authority_seeds = &[
     PREFIX.as_bytes(),
@@ -147,259 +451,754 @@ pub fn process_create_metadata (
     &[metadata_bump_seed]
   ];
 
-  if metadata_key != *metadata_acount_info.key {
-    msg!("Invalid PDA");
-    return Err(ReeMetaError::InvalidInstruction.into())
-  }
-
-  msg!("build Metadata");
-
-  let mut metadata: Metadata<ArtNft> = Metadata{
-    // version: 1,
-    kind: metadata_data.kind,
+  let metadata: Metadata<ArtNft> = Metadata{
+    kind: Kind::RoyaltyArt,
     mint: *mint_account_info.key,
     data: art_nft,
-    is_modifiable: metadata_data.is_modifiable,
-    update_type: metadata_data.update_type,
+    is_modifiable: metaplex.is_mutable,
+    update_type: UpdateType::WalletSigner,
     collection: None,
-    update_authority: match update_authority {
-      None => None,
-      Some(account_info) => Some(*account_info.key),
-    },
+    update_authority: Some(metaplex.update_authority),
   };
 
-  if metadata_data.in_collection {
-    let collection_account_info = next_account_info(account_iter)?;
-    metadata.collection = Some(*collection_account_info.key);
+  let rent = &Rent::from_account_info(rent_info)?;
+
+  create_pda_account(
+    payer_account_info,
+    metadata_account_info,
+    system_info,
+    program_id,
+    rent,
+    metadata.clone().size(),
+    metadata_authority_seeds,
+  )?;
+
+  metadata.serialize(&mut *metadata_account_info.data.borrow_mut())?;
+
+  Ok(())
+}
+
+pub fn process_set_attribute (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: SetAttributeArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+
+  let header = assert_metadata_account(program_id, metadata_account_info)?;
+
+  handler_for(header.kind)?.set_attribute(program_id, accounts, data)
+}
+
+pub fn process_remove_attribute (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: RemoveAttributeArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+
+  let header = assert_metadata_account(program_id, metadata_account_info)?;
+
+  handler_for(header.kind)?.remove_attribute(program_id, accounts, data)
+}
+
+pub fn process_init_multisig (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: InitMultisigArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let multisig_account_info = next_account_info(account_iter)?;
+  let payer_account_info = next_account_info(account_iter)?;
+  let base_account_info = next_account_info(account_iter)?;
+  let system_info = next_account_info(account_iter)?;
+  let rent_info = next_account_info(account_iter)?;
+
+  if *system_info.key != system_program::ID || *rent_info.key != RENT_ID {
+    msg!("invalid system accounts");
+    return Err(ReeMetaError::InvalidInstruction.into())
   }
 
-  msg!("Rent");
-  let rent = &Rent::from_account_info(rent_info)?;
+  if !base_account_info.is_signer {
+    msg!("multisig base must sign");
+    return Err(ReeMetaError::InvalidInstruction.into())
+  }
 
-  let required_lamports = rent
-    .minimum_balance(metadata.clone().size())
-    .max(1)
-    .saturating_sub(metadata_acount_info.lamports());
-
-  if required_lamports > 0 {
-    msg!("Tranfer {} lamports", required_lamports);
-    invoke(
-      &system_instruction::transfer(
-        payer_account_info.key, 
-        metadata_acount_info.key, 
-        required_lamports
-      ),
-      &[
-        payer_account_info.clone(),
-        metadata_acount_info.clone(),
-        system_info.clone(),
-      ]
-    )?;
+  let (multisig_key, multisig_bump_seed) = find_multisig_address(program_id, base_account_info.key);
+  if multisig_key != *multisig_account_info.key {
+    msg!("Invalid PDA");
+    return Err(ReeMetaError::InvalidMultisig.into())
   }
 
-  let accounts = &[
-    metadata_acount_info.clone(),
-    system_info.clone(),
+  let config = MultisigConfig{
+    is_initialized: true,
+    threshold: data.threshold,
+    signers: account_iter.map(|account_info| *account_info.key).collect(),
+  };
+  config.validate()?;
+
+  let multisig_authority_seeds = &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    MULTISIG_PREFIX.as_bytes(),
+    base_account_info.key.as_ref(),
+    &[multisig_bump_seed]
   ];
 
-  msg!("allocate and assign");
-  invoke_signed(
-    &system_instruction::allocate(
-      metadata_acount_info.key, 
-      metadata.clone().size().try_into().unwrap()
-    ), 
-    accounts, 
-    &[metadata_authority_seeds]
+  let rent = &Rent::from_account_info(rent_info)?;
+
+  create_pda_account(
+    payer_account_info,
+    multisig_account_info,
+    system_info,
+    program_id,
+    rent,
+    config.size(),
+    multisig_authority_seeds,
   )?;
-  invoke_signed(
-    &system_instruction::assign(metadata_acount_info.key, program_id),
-    accounts, 
-    &[metadata_authority_seeds]
+
+  config.serialize(&mut *multisig_account_info.data.borrow_mut())?;
+
+  Ok(())
+}
+
+pub fn process_approve_delegate (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: ApproveDelegateArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+  let payer_account_info = next_account_info(account_iter)?;
+  let update_authority_account_info = next_account_info(account_iter)?;
+  let delegate_account_info = next_account_info(account_iter)?;
+  let record_account_info = next_account_info(account_iter)?;
+  let system_info = next_account_info(account_iter)?;
+  let rent_info = next_account_info(account_iter)?;
+
+  if *system_info.key != system_program::ID || *rent_info.key != RENT_ID {
+    msg!("invalid system accounts");
+    return Err(ReeMetaError::InvalidInstruction.into())
+  }
+
+  let header = assert_metadata_account(program_id, metadata_account_info)?;
+
+  if !header.is_modifiable {
+    return Err(ReeMetaError::AlreadyLocked.into())
+  }
+
+  // delegates can not hand out rights themselves
+  assert_update_authority(
+    program_id,
+    &header.update_type,
+    &header.update_authority,
+    update_authority_account_info,
+    accounts,
   )?;
 
-  if *mint_authority_account_info.key != *new_mint_authority_account_info.key {
-    invoke(
-      &spl_token::instruction::set_authority(
-        token_info.key, 
-        mint_account_info.key, 
-        Some(new_mint_authority_account_info.key), 
-        spl_token::instruction::AuthorityType::MintTokens, 
-        mint_authority_account_info.key, 
-        &[mint_authority_account_info.key]
-      )?, 
-      &[
-        mint_account_info.clone(),
-        mint_authority_account_info.clone(),
-      ]
+  if data.scope == 0 || data.scope & !SCOPE_ALL != 0 {
+    msg!("invalid delegate scope {}", data.scope);
+    return Err(ReeMetaError::InvalidDelegate.into())
+  }
+
+  if data.expiry <= Clock::get()?.unix_timestamp {
+    return Err(ReeMetaError::DelegateExpired.into())
+  }
+
+  let (record_key, record_bump_seed) = find_delegate_record_address(
+    program_id,
+    metadata_account_info.key,
+    delegate_account_info.key,
+  );
+  if record_key != *record_account_info.key {
+    msg!("Invalid PDA");
+    return Err(ReeMetaError::InvalidDelegate.into())
+  }
+
+  // approving again replaces the scope and expiry of the existing record
+  if record_account_info.data_is_empty() {
+    let record_seeds = &[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      metadata_account_info.key.as_ref(),
+      DELEGATE_PREFIX.as_bytes(),
+      delegate_account_info.key.as_ref(),
+      &[record_bump_seed]
+    ];
+
+    let rent = &Rent::from_account_info(rent_info)?;
+
+    create_pda_account(
+      payer_account_info,
+      record_account_info,
+      system_info,
+      program_id,
+      rent,
+      DelegateRecord::size(),
+      record_seeds,
     )?;
+  } else {
+    assert_owned_by(record_account_info, program_id)?;
   }
 
-  msg!("write data to account");
-  metadata.serialize(&mut *metadata_acount_info.data.borrow_mut())?;
+  let record = DelegateRecord{
+    is_initialized: true,
+    metadata: *metadata_account_info.key,
+    delegate: *delegate_account_info.key,
+    scope: data.scope,
+    expiry: data.expiry,
+  };
+
+  record.serialize(&mut *record_account_info.data.borrow_mut())?;
 
   Ok(())
 }
 
-pub fn process_mint_nft(
-  _program_id: &Pubkey,
+pub fn process_revoke_delegate (
+  program_id: &Pubkey,
   accounts: &[AccountInfo],
 ) -> ProgramResult {
   let account_iter = &mut accounts.iter();
-  let mint_account_info = next_account_info(account_iter)?;
+  let metadata_account_info = next_account_info(account_iter)?;
   let authority_account_info = next_account_info(account_iter)?;
-  let recipient_token_account_info = next_account_info(account_iter)?;
-  let token_program_info = next_account_info(account_iter)?;
+  let record_account_info = next_account_info(account_iter)?;
+  let receiver_account_info = next_account_info(account_iter)?;
 
-  let mint: Mint = assert_initialized(mint_account_info)?;
+  let header = assert_metadata_account(program_id, metadata_account_info)?;
+  assert_owned_by(record_account_info, program_id)?;
 
-  let recipient_token_account: TokenAccount = assert_initialized(recipient_token_account_info)?;
+  let record = DelegateRecord::from_account_info(record_account_info)?;
 
-  assert_valid_mint_authority(&mint.mint_authority, &authority_account_info)?;
+  if !record.is_initialized || record.metadata != *metadata_account_info.key {
+    return Err(ReeMetaError::InvalidDelegate.into())
+  }
 
-  assert_owned_by(mint_account_info, &spl_token::id())?;
-  assert_owned_by(recipient_token_account_info, &spl_token::id())?;
+  // the delegate may always give up its own rights
+  if record.delegate == *authority_account_info.key {
+    if !authority_account_info.is_signer {
+      return Err(ReeMetaError::InvalidDelegate.into())
+    }
+  } else {
+    assert_update_authority(
+      program_id,
+      &header.update_type,
+      &header.update_authority,
+      authority_account_info,
+      accounts,
+    )?;
+  }
 
-  if recipient_token_account.mint != *mint_account_info.key {
-    return Err(ReeMetaError::InvalidInstruction.into())
+  // the rent goes back to the update authority that approved the delegate, or to
+  // the delegate once the metadata has no update authority left
+  let rent_receiver = header.update_authority.unwrap_or(record.delegate);
+  if *receiver_account_info.key != rent_receiver {
+    msg!("rent of the record returns to {}", rent_receiver.to_string());
+    return Err(ReeMetaError::InvalidDelegate.into())
   }
 
-  // mint the token then remove the mint authority from the mint.
-  invoke(
-    &spl_token::instruction::mint_to(
-      token_program_info.key, 
-      mint_account_info.key, 
-      recipient_token_account_info.key, 
-      authority_account_info.key, 
-      &[authority_account_info.key], 
-      1
-    )?,
-    &[
-      mint_account_info.clone(),
-      recipient_token_account_info.clone(),
-      authority_account_info.clone(),
-    ]
-  )?;
+  // close the record and return its rent
+  let lamports = record_account_info.lamports();
+  **record_account_info.try_borrow_mut_lamports()? -= lamports;
+  **receiver_account_info.try_borrow_mut_lamports()? += lamports;
 
-  invoke(
-    &spl_token::instruction::set_authority(
-      token_program_info.key, 
-      mint_account_info.key, 
-      None, 
-      spl_token::instruction::AuthorityType::MintTokens, 
-      authority_account_info.key, 
-      &[authority_account_info.key]
-    )?,
-    &[
-      mint_account_info.clone(),
-      authority_account_info.clone(),
-    ]
-  )?;
+  record_account_info.data.borrow_mut().fill(0);
 
   Ok(())
 }
 
-pub fn process_lock_nft (
+pub fn process_update_uri (
   program_id: &Pubkey,
   accounts: &[AccountInfo],
+  data: UpdateUriArgs,
 ) -> ProgramResult {
   let account_iter = &mut accounts.iter();
   let metadata_account_info = next_account_info(account_iter)?;
-  let _payer_account_info = next_account_info(account_iter)?;
-  let update_authority_account_info = next_account_info(account_iter)?;
 
-  assert_owned_by(metadata_account_info, program_id)?;
+  let header = assert_metadata_account(program_id, metadata_account_info)?;
+
+  handler_for(header.kind)?.update_uri(program_id, accounts, data)
+}
+
+pub fn process_init_config (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: ConfigArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let config_account_info = next_account_info(account_iter)?;
+  let payer_account_info = next_account_info(account_iter)?;
+  let admin_account_info = next_account_info(account_iter)?;
+  let fee_recipient_account_info = next_account_info(account_iter)?;
+  let system_info = next_account_info(account_iter)?;
+  let rent_info = next_account_info(account_iter)?;
+  let program_data_info = next_account_info(account_iter)?;
+
+  // the first caller becomes admin, so it has to be whoever deployed the program
+  assert_upgrade_authority(program_id, program_data_info, admin_account_info)?;
 
-  let kind = Metadata::<ArtNft>::get_kind(metadata_account_info)?;
+  let (config_key, config_bump_seed) = find_config_address(program_id);
+  if config_key != *config_account_info.key {
+    msg!("Invalid PDA");
+    return Err(ReeMetaError::InvalidConfig.into())
+  }
 
-  match kind {
-    Kind::RoyaltyArt => {
-      artNft::lock_nft(
-        Metadata::<ArtNft>::from_account_info(metadata_account_info)?, 
-        metadata_account_info, 
-        update_authority_account_info
-      )
-    },
-    Kind::Uninitialized => {
-      customNft::lock_nft(
-        Metadata::<CustomNft>::from_account_info(metadata_account_info)?,
-        metadata_account_info, 
-        update_authority_account_info
-      )
-    } 
+  if !config_account_info.data_is_empty() {
+    msg!("program config already initialized");
+    return Err(ReeMetaError::InvalidConfig.into())
   }
+
+  let config = ProgramConfig{
+    is_initialized: true,
+    admin: *admin_account_info.key,
+    protocol_fee: data.protocol_fee,
+    fee_recipient: *fee_recipient_account_info.key,
+    paused: false,
+  };
+  config.validate()?;
+
+  let config_seeds = &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    CONFIG_PREFIX.as_bytes(),
+    &[config_bump_seed]
+  ];
+
+  let rent = &Rent::from_account_info(rent_info)?;
+
+  create_pda_account(
+    payer_account_info,
+    config_account_info,
+    system_info,
+    program_id,
+    rent,
+    ProgramConfig::size(),
+    config_seeds,
+  )?;
+
+  config.serialize(&mut *config_account_info.data.borrow_mut())?;
+
+  Ok(())
 }
 
-pub fn process_add_royalty (
+/// load the program config for an admin instruction, the admin has to sign
+fn load_config_as_admin(
+  program_id: &Pubkey,
+  config_account_info: &AccountInfo,
+  admin_account_info: &AccountInfo,
+) -> Result<ProgramConfig, ProgramError> {
+  let (config_key, _) = find_config_address(program_id);
+  if config_key != *config_account_info.key {
+    msg!("Invalid PDA");
+    return Err(ReeMetaError::InvalidConfig.into())
+  }
+  assert_owned_by(config_account_info, program_id)?;
+
+  let config = ProgramConfig::from_account_info(config_account_info)?;
+  if !config.is_initialized {
+    return Err(ReeMetaError::Uninitialized.into())
+  }
+
+  if !admin_account_info.is_signer || config.admin != *admin_account_info.key {
+    msg!("program config admin must sign");
+    return Err(ReeMetaError::InvalidUpdateAuthority.into())
+  }
+
+  Ok(config)
+}
+
+pub fn process_update_config (
   program_id: &Pubkey,
   accounts: &[AccountInfo],
-  data: AddRoyaltyArgs,
+  data: ConfigArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let config_account_info = next_account_info(account_iter)?;
+  let admin_account_info = next_account_info(account_iter)?;
+  let new_admin_account_info = next_account_info(account_iter)?;
+  let fee_recipient_account_info = next_account_info(account_iter)?;
+
+  let mut config = load_config_as_admin(program_id, config_account_info, admin_account_info)?;
+
+  config.admin = *new_admin_account_info.key;
+  config.protocol_fee = data.protocol_fee;
+  config.fee_recipient = *fee_recipient_account_info.key;
+  config.validate()?;
+
+  config.serialize(&mut *config_account_info.data.borrow_mut())?;
+
+  Ok(())
+}
+
+pub fn process_set_paused (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: SetPausedArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let config_account_info = next_account_info(account_iter)?;
+  let admin_account_info = next_account_info(account_iter)?;
+
+  let mut config = load_config_as_admin(program_id, config_account_info, admin_account_info)?;
+
+  msg!("paused: {}", data.paused);
+  config.paused = data.paused;
+
+  config.serialize(&mut *config_account_info.data.borrow_mut())?;
+
+  Ok(())
+}
+
+pub fn process_quote_royalty (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: QuoteRoyaltyArgs,
 ) -> ProgramResult {
   let account_iter = &mut accounts.iter();
   let metadata_account_info = next_account_info(account_iter)?;
 
-  assert_owned_by(metadata_account_info, program_id)?;
-
-  match Metadata::<ArtNft>::get_kind(metadata_account_info)? {
-    Kind::RoyaltyArt => artNft::add_royalty(
-      accounts, 
-      Metadata::<ArtNft>::from_account_info(metadata_account_info)?, 
-      data
-    ),
-    Kind::Uninitialized => {
-      msg!("This NFT Kind has no royalties");
-      return Err(ReeMetaError::InvalidNFTKind.into())
+  let header = assert_metadata_account(program_id, metadata_account_info)?;
+
+  handler_for(header.kind)?.quote_royalty(program_id, accounts, data)
+}
+
+pub fn process_update_stats (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: UpdateStatsArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+
+  let header = assert_metadata_account(program_id, metadata_account_info)?;
+
+  handler_for(header.kind)?.update_stats(program_id, accounts, data)
+}
+
+pub fn process_revoke (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+
+  let header = assert_metadata_account(program_id, metadata_account_info)?;
+
+  handler_for(header.kind)?.revoke(program_id, accounts)
+}
+
+pub fn process_fractionalize (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: FractionalizeArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let metadata_account_info = next_account_info(account_iter)?;
+
+  let header = assert_metadata_account(program_id, metadata_account_info)?;
+
+  handler_for(header.kind)?.fractionalize(program_id, accounts, data)
+}
+
+/// load a distributor checking it is the PDA of its mint and holder
+fn load_distributor(
+  program_id: &Pubkey,
+  distributor_account_info: &AccountInfo,
+) -> Result<FractionDistributor, ProgramError> {
+  assert_owned_by(distributor_account_info, program_id)?;
+
+  let distributor = FractionDistributor::from_account_info(distributor_account_info)?;
+  if !distributor.is_initialized {
+    return Err(ReeMetaError::Uninitialized.into())
+  }
+
+  let (distributor_key, _) = find_distributor_address(program_id, &distributor.nft_mint, &distributor.holder);
+  if distributor_key != *distributor_account_info.key {
+    return Err(ReeMetaError::InvalidDistributor.into())
+  }
+
+  Ok(distributor)
+}
+
+/// load the stake of owner in the distributor, the stake PDA must exist
+fn load_fraction_stake(
+  program_id: &Pubkey,
+  distributor_account_info: &AccountInfo,
+  stake_account_info: &AccountInfo,
+  owner_account_info: &AccountInfo,
+) -> Result<FractionStake, ProgramError> {
+  let (stake_key, _) = find_fraction_stake_address(program_id, distributor_account_info.key, owner_account_info.key);
+  if stake_key != *stake_account_info.key {
+    return Err(ReeMetaError::InvalidAccountKey.into())
+  }
+  assert_owned_by(stake_account_info, program_id)?;
+
+  let stake = FractionStake::from_account_info(stake_account_info)?;
+  if !stake.is_initialized {
+    return Err(ReeMetaError::Uninitialized.into())
+  }
+
+  Ok(stake)
+}
+
+/// lamports of the distributor above its rent exemption
+fn distributor_available(distributor_account_info: &AccountInfo, rent: &Rent) -> u64 {
+  distributor_account_info.lamports()
+    .saturating_sub(rent.minimum_balance(distributor_account_info.data_len()))
+}
+
+/// the escrow is a fraction token account owned by the distributor
+fn assert_fraction_escrow(
+  distributor_account_info: &AccountInfo,
+  distributor: &FractionDistributor,
+  escrow_account_info: &AccountInfo,
+  token_program_info: &AccountInfo,
+) -> ProgramResult {
+  let escrow: TokenAccount = unpack_token_account(escrow_account_info, token_program_info.key)?;
+  if escrow.owner != *distributor_account_info.key || escrow.mint != distributor.fraction_mint {
+    msg!("escrow must be a fraction token account of the distributor");
+    return Err(ReeMetaError::InvalidTokenAccount.into())
+  }
+
+  Ok(())
+}
+
+pub fn process_deposit_fractions (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: FractionAmountArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let distributor_account_info = next_account_info(account_iter)?;
+  let stake_account_info = next_account_info(account_iter)?;
+  let owner_account_info = next_account_info(account_iter)?;
+  let fraction_mint_account_info = next_account_info(account_iter)?;
+  let owner_token_account_info = next_account_info(account_iter)?;
+  let escrow_account_info = next_account_info(account_iter)?;
+  let token_program_info = next_account_info(account_iter)?;
+  let system_info = next_account_info(account_iter)?;
+  let rent_info = next_account_info(account_iter)?;
+
+  if *system_info.key != system_program::ID || *rent_info.key != RENT_ID {
+    return Err(ReeMetaError::InvalidInstruction.into())
+  }
+  assert_token_program(token_program_info)?;
+
+  if data.amount == 0 {
+    return Err(ReeMetaError::InsufficientStake.into())
+  }
+
+  let mut distributor = load_distributor(program_id, distributor_account_info)?;
+  if *fraction_mint_account_info.key != distributor.fraction_mint {
+    return Err(ReeMetaError::InvalidFractionMint.into())
+  }
+  assert_fraction_escrow(distributor_account_info, &distributor, escrow_account_info, token_program_info)?;
+  let fraction_mint: Mint = unpack_mint(fraction_mint_account_info, token_program_info.key)?;
+
+  let rent = &Rent::from_account_info(rent_info)?;
+  let (stake_key, stake_bump_seed) = find_fraction_stake_address(
+    program_id,
+    distributor_account_info.key,
+    owner_account_info.key,
+  );
+  let mut stake = if stake_account_info.data_is_empty() {
+    if stake_key != *stake_account_info.key {
+      return Err(ReeMetaError::InvalidAccountKey.into())
+    }
+
+    let stake_seeds: &[&[u8]] = &[
+      PREFIX.as_bytes(),
+      program_id.as_ref(),
+      distributor_account_info.key.as_ref(),
+      STAKE_PREFIX.as_bytes(),
+      owner_account_info.key.as_ref(),
+      &[stake_bump_seed]
+    ];
+    create_pda_account(
+      owner_account_info,
+      stake_account_info,
+      system_info,
+      program_id,
+      rent,
+      FractionStake::size(),
+      stake_seeds,
+    )?;
+
+    FractionStake{
+      is_initialized: true,
+      distributor: *distributor_account_info.key,
+      owner: *owner_account_info.key,
+      amount: 0,
+      reward_debt: 0,
+      pending: 0,
     }
+  } else {
+    load_fraction_stake(program_id, distributor_account_info, stake_account_info, owner_account_info)?
+  };
+
+  // payouts so far are split between the staked fractions and the reserve
+  distributor.sync(distributor_available(distributor_account_info, rent))?;
+  stake.settle(&distributor)?;
+
+  invoke(
+    &spl_token_2022::instruction::transfer_checked(
+      token_program_info.key,
+      owner_token_account_info.key,
+      fraction_mint_account_info.key,
+      escrow_account_info.key,
+      owner_account_info.key,
+      &[],
+      data.amount,
+      fraction_mint.decimals
+    )?,
+    &[
+      owner_token_account_info.clone(),
+      fraction_mint_account_info.clone(),
+      escrow_account_info.clone(),
+      owner_account_info.clone(),
+    ]
+  )?;
+
+  stake.deposit(data.amount, &mut distributor)?;
+  msg!("{} fractions staked of {}", stake.amount, distributor.total_staked);
+
+  stake.serialize(&mut *stake_account_info.data.borrow_mut())?;
+  distributor.serialize(&mut *distributor_account_info.data.borrow_mut())?;
+
+  Ok(())
+}
+
+pub fn process_withdraw_fractions (
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+  data: FractionAmountArgs,
+) -> ProgramResult {
+  let account_iter = &mut accounts.iter();
+  let distributor_account_info = next_account_info(account_iter)?;
+  let stake_account_info = next_account_info(account_iter)?;
+  let owner_account_info = next_account_info(account_iter)?;
+  let fraction_mint_account_info = next_account_info(account_iter)?;
+  let owner_token_account_info = next_account_info(account_iter)?;
+  let escrow_account_info = next_account_info(account_iter)?;
+  let token_program_info = next_account_info(account_iter)?;
+
+  assert_token_program(token_program_info)?;
+
+  let mut distributor = load_distributor(program_id, distributor_account_info)?;
+  if *fraction_mint_account_info.key != distributor.fraction_mint {
+    return Err(ReeMetaError::InvalidFractionMint.into())
+  }
+  assert_fraction_escrow(distributor_account_info, &distributor, escrow_account_info, token_program_info)?;
+  let fraction_mint: Mint = unpack_mint(fraction_mint_account_info, token_program_info.key)?;
+
+  let mut stake = load_fraction_stake(program_id, distributor_account_info, stake_account_info, owner_account_info)?;
+  if data.amount == 0 || data.amount > stake.amount {
+    msg!("{} fractions staked", stake.amount);
+    return Err(ReeMetaError::InsufficientStake.into())
   }
 
+  let rent = &Rent::get()?;
+  distributor.sync(distributor_available(distributor_account_info, rent))?;
+  stake.settle(&distributor)?;
+
+  let (_, distributor_bump_seed) = find_distributor_address(program_id, &distributor.nft_mint, &distributor.holder);
+  let distributor_seeds: &[&[u8]] = &[
+    PREFIX.as_bytes(),
+    program_id.as_ref(),
+    distributor.nft_mint.as_ref(),
+    DISTRIBUTOR_PREFIX.as_bytes(),
+    distributor.holder.as_ref(),
+    &[distributor_bump_seed]
+  ];
+
+  invoke_signed(
+    &spl_token_2022::instruction::transfer_checked(
+      token_program_info.key,
+      escrow_account_info.key,
+      fraction_mint_account_info.key,
+      owner_token_account_info.key,
+      distributor_account_info.key,
+      &[],
+      data.amount,
+      fraction_mint.decimals
+    )?,
+    &[
+      escrow_account_info.clone(),
+      fraction_mint_account_info.clone(),
+      owner_token_account_info.clone(),
+      distributor_account_info.clone(),
+    ],
+    &[distributor_seeds]
+  )?;
+
+  stake.withdraw(data.amount, &mut distributor)?;
+  msg!("{} fractions staked of {}", stake.amount, distributor.total_staked);
+
+  stake.serialize(&mut *stake_account_info.data.borrow_mut())?;
+  distributor.serialize(&mut *distributor_account_info.data.borrow_mut())?;
+
+  Ok(())
 }
 
-// TODO: change to tokens
-pub fn process_nft_transaction (
+pub fn process_claim_fractional_royalties (
   program_id: &Pubkey,
   accounts: &[AccountInfo],
-  data: NftTransactionArgs
 ) -> ProgramResult {
   let account_iter = &mut accounts.iter();
-  let metadata_account_info = next_account_info(account_iter)?;
+  let distributor_account_info = next_account_info(account_iter)?;
+  let stake_account_info = next_account_info(account_iter)?;
+  let owner_account_info = next_account_info(account_iter)?;
 
-  assert_owned_by(metadata_account_info, program_id)?;
-
-  let kind = Metadata::<ArtNft>::get_kind(metadata_account_info)?;
-
-  match kind {
-    Kind::RoyaltyArt => {
-      artNft::nft_transaction(
-        accounts, 
-        Metadata::<ArtNft>::from_account_info(metadata_account_info)?, 
-        data
-      )
-    },
-    _ => {
-      // non Royalty transaction transfer full amount to target
-      let payer_account_info = next_account_info(account_iter)?;
-      let target_account_info = next_account_info(account_iter)?;
-      let system_info = next_account_info(account_iter)?;
-
-      if *system_info.key != system_program::ID {
-        msg!("Invalid system account");
-        return Err(ReeMetaError::InvalidInstruction.into())
-      }
+  let mut distributor = load_distributor(program_id, distributor_account_info)?;
+  let mut stake = load_fraction_stake(program_id, distributor_account_info, stake_account_info, owner_account_info)?;
 
-      invoke(
-        &system_instruction::transfer(
-          payer_account_info.key, 
-          target_account_info.key,
-          data.amount
-        ),
-        &[
-          payer_account_info.clone(),
-          target_account_info.clone(),
-          system_info.clone()
-        ]
-      )?;
-
-      Ok(())
+  // royalties credited to the distributor in the vault are swept in first
+  if let Ok(vault_account_info) = next_account_info(account_iter) {
+    let (vault_key, _) = find_royalty_vault_address(program_id, &distributor.nft_mint);
+    if vault_key != *vault_account_info.key {
+      return Err(ReeMetaError::InvalidVault.into())
+    }
+    assert_owned_by(vault_account_info, program_id)?;
+
+    let mut vault = RoyaltyVault::from_account_info(vault_account_info)?;
+    if let Some(balance) = vault.balances.iter_mut()
+      .find(|balance| balance.address == *distributor_account_info.key) {
+        let amount = balance.amount;
+        balance.amount = 0;
+
+        msg!("sweeping {} lamports from the vault", amount);
+        let vault_lamports = vault_account_info.lamports()
+          .checked_sub(amount)
+          .ok_or(ReeMetaError::MathOverflow)?;
+        **vault_account_info.try_borrow_mut_lamports()? = vault_lamports;
+        **distributor_account_info.try_borrow_mut_lamports()? += amount;
+        vault.serialize(&mut *vault_account_info.data.borrow_mut())?;
     }
   }
 
-}
\ No newline at end of file
+  let rent = &Rent::get()?;
+  distributor.sync(distributor_available(distributor_account_info, rent))?;
+  stake.settle(&distributor)?;
+
+  let amount = stake.pending;
+  if amount == 0 {
+    return Err(ReeMetaError::NothingToClaim.into())
+  }
+  stake.pending = 0;
+  distributor.tracked = distributor.tracked.saturating_sub(amount);
+
+  msg!("claiming {} lamports for {}", amount, owner_account_info.key.to_string());
+
+  // the distributor is owned by this program so lamports can be moved directly
+  let distributor_lamports = distributor_account_info.lamports()
+    .checked_sub(amount)
+    .ok_or(ReeMetaError::MathOverflow)?;
+  **distributor_account_info.try_borrow_mut_lamports()? = distributor_lamports;
+  **owner_account_info.try_borrow_mut_lamports()? += amount;
+
+  stake.serialize(&mut *stake_account_info.data.borrow_mut())?;
+  distributor.serialize(&mut *distributor_account_info.data.borrow_mut())?;
+
+  Ok(())
+}
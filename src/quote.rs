@@ -0,0 +1,415 @@
+use crate::{
+  error::ReeMetaError,
+  instruction::NftTransactionArgs,
+  state::{
+    Metadata,
+    ArtNft,
+    ProgramConfig,
+    Royalty,
+    BASIS_POINTS,
+    MAX_RESALE_FEE,
+    MAX_MARKETPLACE_FEE_BPS,
+    MAX_REFERRAL_FEE_BPS,
+  },
+};
+
+use borsh::{BorshSerialize, BorshDeserialize};
+
+use solana_program::{
+  entrypoint::ProgramResult,
+  msg,
+  program_error::ProgramError,
+  pubkey::Pubkey,
+};
+
+use percentage::Percentage;
+
+/// lamports going to one address in a sale
+#[derive(PartialEq, Debug, Clone, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Payout {
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+  pub address: Pubkey,
+  pub amount: u64,
+}
+
+/// how a sale amount is split. the program pays out exactly this plan, a payout that would
+/// leave its recipient below rent exemption is credited to them in the royalty vault instead
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PayoutPlan {
+  pub amount: u64,
+  pub initial_sale: bool, // true when this sale completes the initial sale
+  pub fees: Vec<Payout>, // in the order the fees were given
+  pub royalties: Vec<Payout>, // one per sale recipient in order, primary split on the initial sale
+  pub royalty_vault: bool, // royalties are credited in the vault rather than paid
+  pub seller: u64,
+}
+
+/// the royalties due on a sale and what is left for the seller, returned by QuoteRoyalty
+#[derive(PartialEq, Debug, Clone, BorshSerialize, BorshDeserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoyaltyQuote {
+  pub royalties: Vec<Payout>,
+  pub seller: u64,
+}
+
+impl PayoutPlan {
+  pub fn royalty_quote(&self) -> RoyaltyQuote {
+    RoyaltyQuote{
+      royalties: self.royalties.clone(),
+      seller: self.seller,
+    }
+  }
+
+  pub fn fee_total(&self) -> u64 {
+    self.fees.iter().map(|fee| fee.amount).sum()
+  }
+
+  pub fn royalty_total(&self) -> u64 {
+    self.royalties.iter().map(|royalty| royalty.amount).sum()
+  }
+}
+
+/// the fee in basis points of the sale amount
+pub fn basis_points_of(amount: u64, basis_points: u16) -> u64 {
+  (amount as u128 * basis_points as u128 / BASIS_POINTS as u128) as u64
+}
+
+/// split the payout between the royalty holders by share using the largest remainder method.
+/// every holder gets the floor of their exact share, then the lamports left over go one
+/// each to the holders with the largest remainders, the earlier holder winning a tie.
+pub fn distribute_by_share(royalties: &Vec<Royalty>, payout: u64) -> Vec<u64> {
+  let total_shares: u128 = royalties.iter().map(|r| r.share as u128).sum();
+  if total_shares == 0 {
+    return vec![0; royalties.len()];
+  }
+
+  let mut amounts: Vec<u64> = Vec::with_capacity(royalties.len());
+  let mut remainders: Vec<(u128, usize)> = Vec::with_capacity(royalties.len());
+  let mut distributed: u64 = 0;
+  for (i, royalty) in royalties.iter().enumerate() {
+    let exact = payout as u128 * royalty.share as u128;
+    let amount = (exact / total_shares) as u64;
+    remainders.push((exact % total_shares, i));
+    distributed += amount;
+    amounts.push(amount);
+  }
+
+  remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+  let leftover = (payout - distributed) as usize;
+  for (_, i) in remainders.iter().take(leftover) {
+    amounts[*i] += 1;
+  }
+
+  return amounts;
+}
+
+/// check the marketplace and referral fees of a sale against their caps. the protocol fee
+/// is capped when the config is written. the whole initial sale goes to the royalties or the
+/// primary split, there is no seller's portion to take these fees from
+pub fn validate_sale_fees(metadata: &Metadata<ArtNft>, data: &NftTransactionArgs) -> ProgramResult {
+  if !metadata.data.initial_sale && (data.marketplace_fee.is_some() || data.referral_fee.is_some()) {
+    msg!("marketplace and referral fees are not taken on the initial sale");
+    return Err(ReeMetaError::InvalidSaleFee.into())
+  }
+
+  for (fee, max_basis_points) in [
+    (&data.marketplace_fee, MAX_MARKETPLACE_FEE_BPS),
+    (&data.referral_fee, MAX_REFERRAL_FEE_BPS),
+  ] {
+    if let Some(fee) = fee {
+      if fee.basis_points > max_basis_points {
+        msg!("sale fee of {} basis points is over the cap of {}", fee.basis_points, max_basis_points);
+        return Err(ReeMetaError::InvalidSaleFee.into())
+      }
+    }
+  }
+
+  Ok(())
+}
+
+/// quote a sale without marketplace or referral fees. the protocol fee of the config is
+/// taken like on chain, pass ProgramConfig::default() when the config doesn't exist
+pub fn quote_payout(
+  metadata: &Metadata<ArtNft>,
+  amount: u64,
+  config: &ProgramConfig,
+) -> Result<PayoutPlan, ProgramError> {
+  match config.protocol_fee {
+    0 => quote_sale(metadata, amount, &[]),
+    basis_points => quote_sale(metadata, amount, &[(config.fee_recipient, basis_points)]),
+  }
+}
+
+/// quote a sale with sale fees given as recipient and basis points. on a resale the fees come
+/// out of what the seller receives after the resale fee. the initial sale only takes the
+/// protocol fee, off the amount before it is split between the royalties or the primary split
+pub fn quote_sale(
+  metadata: &Metadata<ArtNft>,
+  amount: u64,
+  fees: &[(Pubkey, u16)],
+) -> Result<PayoutPlan, ProgramError> {
+  if metadata.data.resale_fee > MAX_RESALE_FEE {
+    return Err(ReeMetaError::InvalidResaleFee.into())
+  }
+
+  let fees: Vec<Payout> = fees.iter().map(|(address, basis_points)| Payout{
+    address: *address,
+    amount: basis_points_of(amount, *basis_points),
+  }).collect();
+  let fees_payout: u64 = fees.iter()
+    .try_fold(0u64, |total, fee| total.checked_add(fee.amount))
+    .ok_or(ReeMetaError::MathOverflow)?;
+
+  let royalty_payout: u64;
+  let mut seller_payout: u64 = 0;
+
+  if !metadata.data.initial_sale {
+    // initial sale has not been done yet all goes to royalties, the protocol fee comes off the top
+    royalty_payout = amount
+      .checked_sub(fees_payout)
+      .ok_or(ReeMetaError::InvalidSaleFee)?;
+  } else {
+    // initial sale occured this is a secondary market transaction
+    let percentage_rate = Percentage::from(metadata.data.resale_fee);
+    royalty_payout = percentage_rate.apply_to(amount);
+    seller_payout = amount
+      .checked_sub(royalty_payout)
+      .and_then(|seller_portion| seller_portion.checked_sub(fees_payout))
+      .ok_or(ReeMetaError::InvalidSaleFee)?;
+  }
+
+  // the primary split replaces the royalties for the initial sale when it is set
+  let recipients = metadata.data.sale_recipients();
+  let royalties: Vec<Payout> = recipients.iter()
+    .zip(distribute_by_share(recipients, royalty_payout).into_iter())
+    .map(|(royalty, amount)| Payout{ address: royalty.address, amount })
+    .collect();
+
+  Ok(PayoutPlan{
+    amount,
+    initial_sale: !metadata.data.initial_sale,
+    fees,
+    royalties,
+    royalty_vault: metadata.data.royalty_vault,
+    seller: seller_payout,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    instruction::SaleFee,
+    state::{Kind, UpdateType},
+  };
+  use assert_matches::assert_matches;
+
+  fn royalty(share: u16) -> Royalty {
+    Royalty{ address: Pubkey::new_unique(), share, verified: true }
+  }
+
+  fn art_nft(resale_fee: u16, initial_sale: bool, royalties: Vec<Royalty>) -> Metadata<ArtNft> {
+    Metadata{
+      kind: Kind::RoyaltyArt,
+      mint: Pubkey::new_unique(),
+      is_modifiable: true,
+      update_type: UpdateType::None,
+      collection: None,
+      update_authority: None,
+      data: ArtNft{
+        name: "art".to_string(),
+        symbol: "ART".to_string(),
+        uri: "https://example.com/art.json".to_string(),
+        resale_fee,
+        initial_sale,
+        collection: None,
+        royalties,
+        royalty_vault: false,
+        primary_split: Vec::new(),
+        enforced: false,
+        attributes: Vec::new(),
+        soulbound: false,
+      },
+    }
+  }
+
+  fn sale(marketplace_fee: Option<u16>, referral_fee: Option<u16>) -> NftTransactionArgs {
+    let sale_fee = |basis_points| SaleFee{ recipient: Pubkey::new_unique(), basis_points };
+    NftTransactionArgs{
+      amount: 1_000_000,
+      marketplace_fee: marketplace_fee.map(sale_fee),
+      referral_fee: referral_fee.map(sale_fee),
+    }
+  }
+
+  #[test]
+  fn test_largest_remainder() {
+    let royalties = vec![royalty(50), royalty(30), royalty(20)];
+    // exact shares of 3.5, 2.1 and 1.4, the leftover lamport goes to the largest remainder
+    assert_eq!(distribute_by_share(&royalties, 7), vec![4, 2, 1]);
+    assert_eq!(distribute_by_share(&royalties, 1_000), vec![500, 300, 200]);
+    assert_eq!(distribute_by_share(&royalties, 0), vec![0, 0, 0]);
+  }
+
+  #[test]
+  fn test_largest_remainder_tie_goes_to_the_earlier_holder() {
+    let royalties = vec![royalty(1), royalty(1), royalty(1)];
+    assert_eq!(distribute_by_share(&royalties, 100), vec![34, 33, 33]);
+    assert_eq!(distribute_by_share(&royalties, 101), vec![34, 34, 33]);
+  }
+
+  #[test]
+  fn test_largest_remainder_pays_out_everything() {
+    let royalties = vec![royalty(33), royalty(33), royalty(17), royalty(17)];
+    for payout in [1, 3, 99, 1_001, u64::MAX] {
+      let amounts = distribute_by_share(&royalties, payout);
+      assert_eq!(amounts.iter().map(|amount| *amount as u128).sum::<u128>(), payout as u128);
+    }
+  }
+
+  #[test]
+  fn test_quote_payout() {
+    let metadata = art_nft(10, true, vec![royalty(60), royalty(40)]);
+    let plan = quote_payout(&metadata, 10_001, &ProgramConfig::default()).unwrap();
+
+    assert!(!plan.initial_sale);
+    assert!(plan.fees.is_empty());
+    assert_eq!(plan.royalties.iter().map(|royalty| royalty.amount).collect::<Vec<u64>>(), vec![600, 400]);
+    assert_eq!(plan.seller, 10_001 - 1_000);
+  }
+
+  #[test]
+  fn test_quote_payout_takes_the_protocol_fee() {
+    let metadata = art_nft(10, true, vec![royalty(100)]);
+    let config = ProgramConfig{
+      is_initialized: true,
+      protocol_fee: 100,
+      fee_recipient: Pubkey::new_unique(),
+      ..ProgramConfig::default()
+    };
+    let plan = quote_payout(&metadata, 10_000, &config).unwrap();
+
+    assert_eq!(plan.fees, vec![Payout{ address: config.fee_recipient, amount: 100 }]);
+    assert_eq!(plan.royalty_total(), 1_000);
+    assert_eq!(plan.seller, 8_900);
+  }
+
+  #[test]
+  fn test_sale_fee_caps() {
+    let metadata = art_nft(10, true, vec![royalty(100)]);
+    assert_matches!(validate_sale_fees(&metadata, &sale(None, None)), Ok(()));
+    assert_matches!(
+      validate_sale_fees(&metadata, &sale(Some(MAX_MARKETPLACE_FEE_BPS), Some(MAX_REFERRAL_FEE_BPS))),
+      Ok(())
+    );
+    assert_eq!(
+      validate_sale_fees(&metadata, &sale(Some(MAX_MARKETPLACE_FEE_BPS + 1), None)),
+      Err(ReeMetaError::InvalidSaleFee.into()),
+    );
+    assert_eq!(
+      validate_sale_fees(&metadata, &sale(None, Some(MAX_REFERRAL_FEE_BPS + 1))),
+      Err(ReeMetaError::InvalidSaleFee.into()),
+    );
+  }
+
+  #[test]
+  fn test_no_marketplace_or_referral_fee_on_the_initial_sale() {
+    let metadata = art_nft(10, false, vec![royalty(100)]);
+    assert_matches!(validate_sale_fees(&metadata, &sale(None, None)), Ok(()));
+    assert_eq!(
+      validate_sale_fees(&metadata, &sale(Some(100), None)),
+      Err(ReeMetaError::InvalidSaleFee.into()),
+    );
+    assert_eq!(
+      validate_sale_fees(&metadata, &sale(None, Some(100))),
+      Err(ReeMetaError::InvalidSaleFee.into()),
+    );
+  }
+
+  #[test]
+  fn test_resale_fees_come_from_the_seller() {
+    let metadata = art_nft(10, true, vec![royalty(100)]);
+    let protocol = Pubkey::new_unique();
+    let marketplace = Pubkey::new_unique();
+    let plan = quote_sale(&metadata, 10_000, &[(protocol, 100), (marketplace, 250)]).unwrap();
+
+    assert_eq!(plan.fees, vec![
+      Payout{ address: protocol, amount: 100 },
+      Payout{ address: marketplace, amount: 250 },
+    ]);
+    assert_eq!(plan.royalty_total(), 1_000);
+    assert_eq!(plan.seller, 10_000 - 1_000 - 350);
+  }
+
+  #[test]
+  fn test_initial_sale_protocol_fee_comes_off_the_top() {
+    let metadata = art_nft(10, false, vec![royalty(100)]);
+    let plan = quote_sale(&metadata, 10_000, &[(Pubkey::new_unique(), 500)]).unwrap();
+
+    assert!(plan.initial_sale);
+    assert_eq!(plan.fee_total(), 500);
+    assert_eq!(plan.royalty_total(), 9_500);
+    assert_eq!(plan.seller, 0);
+  }
+
+  #[test]
+  fn test_fees_larger_than_the_seller_portion() {
+    let metadata = art_nft(100, true, vec![royalty(100)]);
+    assert_eq!(
+      quote_sale(&metadata, 10_000, &[(Pubkey::new_unique(), 1)]),
+      Err(ReeMetaError::InvalidSaleFee.into()),
+    );
+  }
+
+  #[test]
+  fn test_fee_total_overflow() {
+    let metadata = art_nft(0, false, vec![royalty(100)]);
+    let fees = [(Pubkey::new_unique(), BASIS_POINTS), (Pubkey::new_unique(), BASIS_POINTS)];
+    assert_eq!(
+      quote_sale(&metadata, u64::MAX, &fees),
+      Err(ReeMetaError::MathOverflow.into()),
+    );
+  }
+
+  #[test]
+  fn test_royalty_quote() {
+    let royalties = vec![royalty(60), royalty(40)];
+    let metadata = art_nft(10, true, royalties.clone());
+    let quote = quote_payout(&metadata, 10_000, &ProgramConfig::default()).unwrap().royalty_quote();
+
+    assert_eq!(quote, RoyaltyQuote{
+      royalties: vec![
+        Payout{ address: royalties[0].address, amount: 600 },
+        Payout{ address: royalties[1].address, amount: 400 },
+      ],
+      seller: 9_000,
+    });
+    // the client reads the quote back from the return data
+    assert_eq!(RoyaltyQuote::try_from_slice(&quote.try_to_vec().unwrap()).unwrap(), quote);
+  }
+
+  #[test]
+  fn test_royalty_quote_leaves_the_protocol_fee_out() {
+    let royalties = vec![royalty(60), royalty(40)];
+    let metadata = art_nft(10, true, royalties.clone());
+    let config = ProgramConfig{
+      is_initialized: true,
+      protocol_fee: 250,
+      fee_recipient: Pubkey::new_unique(),
+      ..ProgramConfig::default()
+    };
+    let quote = quote_payout(&metadata, 10_000, &config).unwrap().royalty_quote();
+
+    // the royalties are unchanged and the fee only comes out of the seller remainder
+    assert_eq!(quote, RoyaltyQuote{
+      royalties: vec![
+        Payout{ address: royalties[0].address, amount: 600 },
+        Payout{ address: royalties[1].address, amount: 400 },
+      ],
+      seller: 9_000 - 250,
+    });
+    assert!(quote.royalties.iter().all(|royalty| royalty.address != config.fee_recipient));
+  }
+}
@@ -0,0 +1,56 @@
+//! serde helpers encoding Pubkeys as base58 strings, used with
+//! `#[serde(with = "crate::serde_pubkey")]` on the state and instruction types
+use std::str::FromStr;
+
+use serde::{de::Error, Deserialize, Deserializer, Serializer};
+use solana_program::pubkey::Pubkey;
+
+pub fn serialize<S: Serializer>(key: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+  serializer.serialize_str(&key.to_string())
+}
+
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+  let key = String::deserialize(deserializer)?;
+  Pubkey::from_str(&key).map_err(|err| D::Error::custom(format!("invalid pubkey {}: {}", key, err)))
+}
+
+pub mod option {
+  use super::*;
+
+  pub fn serialize<S: Serializer>(key: &Option<Pubkey>, serializer: S) -> Result<S::Ok, S::Error> {
+    match key {
+      None => serializer.serialize_none(),
+      Some(key) => serializer.serialize_some(&key.to_string()),
+    }
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Pubkey>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+      None => Ok(None),
+      Some(key) => Pubkey::from_str(&key)
+        .map(Some)
+        .map_err(|err| D::Error::custom(format!("invalid pubkey {}: {}", key, err))),
+    }
+  }
+}
+
+pub mod vec {
+  use super::*;
+  use serde::ser::SerializeSeq;
+
+  pub fn serialize<S: Serializer>(keys: &Vec<Pubkey>, serializer: S) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(Some(keys.len()))?;
+    for key in keys.iter() {
+      seq.serialize_element(&key.to_string())?;
+    }
+    seq.end()
+  }
+
+  pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Pubkey>, D::Error> {
+    Vec::<String>::deserialize(deserializer)?
+      .iter()
+      .map(|key| Pubkey::from_str(key)
+        .map_err(|err| D::Error::custom(format!("invalid pubkey {}: {}", key, err))))
+      .collect()
+  }
+}
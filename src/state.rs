@@ -1,20 +1,82 @@
 use solana_program::{
   borsh::try_from_slice_unchecked,
   account_info::AccountInfo,
+  clock::UnixTimestamp,
+  entrypoint::ProgramResult,
   program_error::ProgramError,
   pubkey::Pubkey,
+  msg,
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 
+use crate::{error::ReeMetaError, pda};
+
 pub const PREFIX: &str = "ree-metadata";
+pub const VAULT_PREFIX: &str = "royalty-vault";
+pub const FREEZE_PREFIX: &str = "freeze-authority";
+pub const MULTISIG_PREFIX: &str = "multisig";
+pub const DELEGATE_PREFIX: &str = "delegate";
+pub const CONFIG_PREFIX: &str = "config";
+pub const DISTRIBUTOR_PREFIX: &str = "distributor";
+pub const STAKE_PREFIX: &str = "stake";
+
+pub const MAX_RESALE_FEE: u16 = 100;
+pub const TOTAL_SHARES: u16 = 100;
+
+// sale fees are in basis points of the sale amount
+pub const BASIS_POINTS: u16 = 10_000;
+pub const MAX_MARKETPLACE_FEE_BPS: u16 = 1_000;
+pub const MAX_REFERRAL_FEE_BPS: u16 = 500;
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 500;
+pub const MAX_ROYALTIES: usize = 16;
+// same limits as Metaplex so imported metadata always fits
+pub const MAX_NAME_LEN: usize = 32;
+pub const MAX_SYMBOL_LEN: usize = 10;
+pub const MAX_URI_LEN: usize = 200;
+pub const MAX_ATTRIBUTES: usize = 32;
+pub const MAX_ATTRIBUTE_KEY_LEN: usize = 32;
+pub const MAX_ATTRIBUTE_VALUE_LEN: usize = 64;
+pub const MAX_MULTISIG_SIGNERS: usize = 11;
+pub const MAX_STATS: usize = 32;
+pub const MAX_STAT_KEY_LEN: usize = 32;
+pub const MAX_STAT_TEXT_LEN: usize = 64;
+// fixed point scale of the lamports earned per staked fraction
+pub const REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+// delegate scopes, combined as a bitmask
+pub const SCOPE_URI: u8 = 1;
+pub const SCOPE_ATTRIBUTES: u8 = 2;
+pub const SCOPE_ROYALTIES: u8 = 4;
+pub const SCOPE_LOCK: u8 = 8;
+pub const SCOPE_ALL: u8 = SCOPE_URI | SCOPE_ATTRIBUTES | SCOPE_ROYALTIES | SCOPE_LOCK;
 
 pub trait MetadataData {
   fn size(&self) -> usize;
+
+  /// bytes of the fields appended to the data after its first release. every appended
+  /// field is its default when zeroed, so data written before them reads as zero padded
+  fn appended_size() -> usize where Self: Sized {
+    0
+  }
+}
+
+/// deserialize data written before padding bytes of appended fields existed
+fn try_from_padded_slice<T: BorshDeserialize>(data: &[u8], padding: usize) -> Result<T, ProgramError> {
+  if padding == 0 {
+    return Ok(try_from_slice_unchecked(data)?)
+  }
+
+  let mut padded = Vec::with_capacity(data.len() + padding);
+  padded.extend_from_slice(data);
+  padded.resize(data.len() + padding, 0);
+  Ok(try_from_slice_unchecked(&padded)?)
 }
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Royalty {
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
   pub address: Pubkey,
   pub share: u16, // 2
   pub verified: bool // 1
@@ -24,8 +86,48 @@ impl Royalty {
   pub fn size() -> usize {3 + 32}
 }
 
+/// check a royalty list: no more than MAX_ROYALTIES entries, no address listed
+/// twice, no zero shares and shares adding up to TOTAL_SHARES
+pub fn validate_royalties(royalties: &Vec<Royalty>) -> ProgramResult {
+  if royalties.len() > MAX_ROYALTIES {
+    msg!("{} royalties exceeds the max of {}", royalties.len(), MAX_ROYALTIES);
+    return Err(ReeMetaError::TooManyRoyalties.into())
+  }
+
+  let mut total: u32 = 0;
+  for (i, royalty) in royalties.iter().enumerate() {
+    if royalty.share == 0 {
+      return Err(ReeMetaError::ZeroShare.into())
+    }
+
+    if royalties[..i].iter().any(|r| r.address == royalty.address) {
+      msg!("royalty {} listed twice", royalty.address.to_string());
+      return Err(ReeMetaError::DuplicateRoyaltyAddress.into())
+    }
+
+    total += royalty.share as u32;
+  }
+
+  if total != TOTAL_SHARES as u32 {
+    msg!("royalty shares total {} instead of {}", total, TOTAL_SHARES);
+    return Err(ReeMetaError::InvalidShareTotal.into())
+  }
+
+  Ok(())
+}
+
+/// check the name, symbol and uri lengths
+pub fn validate_strings(name: &str, symbol: &str, uri: &str) -> ProgramResult {
+  if name.len() > MAX_NAME_LEN || symbol.len() > MAX_SYMBOL_LEN || uri.len() > MAX_URI_LEN {
+    msg!("name, symbol and uri are limited to {}, {} and {} bytes", MAX_NAME_LEN, MAX_SYMBOL_LEN, MAX_URI_LEN);
+    return Err(ReeMetaError::StringTooLong.into())
+  }
+  Ok(())
+}
+
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CustomNft {
   pub data: Vec<u8>
 }
@@ -38,14 +140,21 @@ impl MetadataData for CustomNft {
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArtNft {
   pub name: String, //4 + len
   pub symbol: String, //4 + len
   pub uri: String, //4 + len
   pub resale_fee: u16, // 2
   pub initial_sale: bool,
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey::option"))]
   pub collection: Option<Pubkey>,
   pub royalties: Vec<Royalty>, // 1 + 4 + (Royaty * len)
+  pub royalty_vault: bool, // 1 royalty payouts accrue in the vault PDA
+  pub primary_split: Vec<Royalty>, // 4 + (Royalty * len) paid on the initial sale when not empty
+  pub enforced: bool, // 1 token accounts stay frozen and only move through ree-meta
+  pub attributes: Vec<(String, String)>, // 4 + ((4 + key len + 4 + value len) * len)
+  pub soulbound: bool, // 1 token accounts stay frozen, never sold or transferred, the issuer can revoke
 }
 
 impl MetadataData for ArtNft {
@@ -67,28 +176,234 @@ impl MetadataData for ArtNft {
 
     size += 4 + Royalty::size() * self.royalties.len();
 
+    size += 1; // royalty vault boolean
+
+    size += 4 + Royalty::size() * self.primary_split.len();
+
+    size += 1; // enforced boolean
+
+    size += 4; // attributes vec size buffer
+    for (key, value) in self.attributes.iter() {
+      size += 4 + key.len() + 4 + value.len();
+    }
+
+    size += 1; // soulbound boolean
+
     return size;
   }
+
+  fn appended_size() -> usize {
+    1 // royalty vault boolean
+    + 4 // primary split vec size buffer
+    + 1 // enforced boolean
+    + 4 // attributes vec size buffer
+    + 1 // soulbound boolean
+  }
 }
 
 impl ArtNft {
   pub fn from_slice(data: &[u8]) -> Result<ArtNft, ProgramError> {
-    let an: ArtNft = try_from_slice_unchecked(data)?;
+    let an: ArtNft = try_from_padded_slice(data, ArtNft::appended_size())?;
     Ok(an)
   }
+
+  /// enforce the fee and royalty invariants, called before any change is written
+  pub fn validate(&self) -> ProgramResult {
+    if self.resale_fee > MAX_RESALE_FEE {
+      msg!("resale fee {} exceeds {}", self.resale_fee, MAX_RESALE_FEE);
+      return Err(ReeMetaError::InvalidResaleFee.into())
+    }
+
+    validate_strings(&self.name, &self.symbol, &self.uri)?;
+    validate_royalties(&self.royalties)?;
+
+    if self.primary_split.len() > 0 {
+      validate_royalties(&self.primary_split)?;
+    }
+
+    if self.attributes.len() > MAX_ATTRIBUTES {
+      msg!("{} attributes exceeds the max of {}", self.attributes.len(), MAX_ATTRIBUTES);
+      return Err(ReeMetaError::TooManyAttributes.into())
+    }
+
+    for (key, value) in self.attributes.iter() {
+      if key.len() == 0 || key.len() > MAX_ATTRIBUTE_KEY_LEN || value.len() > MAX_ATTRIBUTE_VALUE_LEN {
+        msg!("attribute {} is empty or too long", key);
+        return Err(ReeMetaError::InvalidAttribute.into())
+      }
+    }
+
+    Ok(())
+  }
+
+  pub fn get_attribute(&self, key: &str) -> Option<&String> {
+    self.attributes.iter()
+      .find(|(k, _)| k == key)
+      .map(|(_, value)| value)
+  }
+
+  /// recipients of the next sale, the primary split is only used before the initial sale
+  pub fn sale_recipients(&self) -> &Vec<Royalty> {
+    if !self.initial_sale && self.primary_split.len() > 0 {
+      return &self.primary_split
+    }
+    &self.royalties
+  }
+}
+
+/// value of a game item stat
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StatValue {
+  Int(i64),
+  Bool(bool),
+  Text(String),
+}
+
+impl StatValue {
+  pub fn size(&self) -> usize {
+    1 + match self { // 1 variant
+      StatValue::Int(_) => 8,
+      StatValue::Bool(_) => 1,
+      StatValue::Text(text) => 4 + text.len(),
+    }
+  }
+}
+
+/// an NFT with stats that change in play. only the game authority writes the stats and it
+/// can keep doing so after the metadata is locked
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameItem {
+  pub name: String, //4 + len
+  pub symbol: String, //4 + len
+  pub uri: String, //4 + len
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+  pub game_authority: Pubkey, // 32 wallet or PDA of the game program
+  pub stats: Vec<(String, StatValue)>, // 4 + ((4 + key len + value size) * len)
+}
+
+impl MetadataData for GameItem {
+  fn size(&self) -> usize {
+    let mut size = 4 // name string size buffer
+    + self.name.len()
+    + 4 // symbol string size buffer
+    + self.symbol.len()
+    + 4 // uri string size buffer
+    + self.uri.len()
+    + 32; // game authority
+
+    size += 4; // stats vec size buffer
+    for (key, value) in self.stats.iter() {
+      size += 4 + key.len() + value.size();
+    }
+
+    return size;
+  }
+}
+
+impl GameItem {
+  pub fn validate(&self) -> ProgramResult {
+    validate_strings(&self.name, &self.symbol, &self.uri)?;
+
+    if self.stats.len() > MAX_STATS {
+      msg!("{} stats exceeds the max of {}", self.stats.len(), MAX_STATS);
+      return Err(ReeMetaError::InvalidStat.into())
+    }
+
+    for (key, value) in self.stats.iter() {
+      let too_long = match value {
+        StatValue::Text(text) => text.len() > MAX_STAT_TEXT_LEN,
+        _ => false,
+      };
+      if key.len() == 0 || key.len() > MAX_STAT_KEY_LEN || too_long {
+        msg!("stat {} is empty or too long", key);
+        return Err(ReeMetaError::InvalidStat.into())
+      }
+    }
+
+    Ok(())
+  }
+
+  pub fn get_stat(&self, key: &str) -> Option<&StatValue> {
+    self.stats.iter()
+      .find(|(k, _)| k == key)
+      .map(|(_, value)| value)
+  }
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoyaltyBalance {
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+  pub address: Pubkey,
+  pub amount: u64, // 8 lamports, sales are only paid in SOL so there are no token balances
+}
+
+impl RoyaltyBalance {
+  pub fn size() -> usize {32 + 8}
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoyaltyVault {
+  pub is_initialized: bool,
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+  pub mint: Pubkey,
+  pub balances: Vec<RoyaltyBalance>, // 4 + (RoyaltyBalance * len)
+}
+
+impl RoyaltyVault {
+  pub fn from_account_info(account_info: &AccountInfo) -> Result<RoyaltyVault, ProgramError> {
+    let data = &account_info.data.borrow();
+    let vault: RoyaltyVault = try_from_slice_unchecked(data)?;
+    Ok(vault)
+  }
+
+  pub fn find_program_address(program_id: &Pubkey, mint_id: &Pubkey) -> (Pubkey, u8) {
+    pda::find_royalty_vault_address(program_id, mint_id)
+  }
+
+  /// add lamports to the running balance of a royalty holder
+  pub fn credit(&mut self, address: &Pubkey, amount: u64) {
+    match self.balances.iter_mut().find(|b| b.address == *address) {
+      Some(balance) => balance.amount += amount,
+      None => self.balances.push(RoyaltyBalance{address: *address, amount}),
+    }
+  }
+
+  /// lamports held in the vault for the royalty holders, on top of its rent exemption
+  pub fn total_balance(&self) -> Result<u64, ProgramError> {
+    self.balances.iter()
+      .try_fold(0u64, |total, balance| total.checked_add(balance.amount))
+      .ok_or(ReeMetaError::MathOverflow.into())
+  }
+
+  pub fn size(&self) -> usize {
+    1 // initialized boolean
+    + 32 // pubkey mint
+    + 4 + RoyaltyBalance::size() * self.balances.len()
+  }
 }
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Kind {
   Uninitialized,
   RoyaltyArt,
+  GameItem,
 }
 
 impl From<&u8> for Kind {
   fn from(orig: &u8) -> Self {
     match orig {
       1 => Kind::RoyaltyArt,
+      2 => Kind::GameItem,
       _ => Kind::Uninitialized,
     }
   }
@@ -96,10 +411,12 @@ impl From<&u8> for Kind {
 
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UpdateType {
   None,
   WalletSigner,
   NftToken,
+  Multisig,
 }
 
 impl From<&u8> for UpdateType {
@@ -107,23 +424,386 @@ impl From<&u8> for UpdateType {
     match orig {
       1 => UpdateType::WalletSigner,
       2 => UpdateType::NftToken,
+      3 => UpdateType::Multisig,
       _ => UpdateType::None,
     }
   }
 }
 
+/// m of n update authority, the metadata update_authority is the key of this account
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultisigConfig {
+  pub is_initialized: bool,
+  pub threshold: u8,
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey::vec"))]
+  pub signers: Vec<Pubkey>, // 4 + (32 * len)
+}
+
+impl MultisigConfig {
+  pub fn from_account_info(account_info: &AccountInfo) -> Result<MultisigConfig, ProgramError> {
+    let data = &account_info.data.borrow();
+    let config: MultisigConfig = try_from_slice_unchecked(data)?;
+    Ok(config)
+  }
+
+  pub fn find_program_address(program_id: &Pubkey, base: &Pubkey) -> (Pubkey, u8) {
+    pda::find_multisig_address(program_id, base)
+  }
+
+  /// threshold between 1 and the number of signers, no signer listed twice
+  pub fn validate(&self) -> ProgramResult {
+    if self.signers.len() > MAX_MULTISIG_SIGNERS
+      || self.threshold == 0
+      || self.threshold as usize > self.signers.len()
+    {
+      msg!("threshold of {} with {} signers is invalid", self.threshold, self.signers.len());
+      return Err(ReeMetaError::InvalidMultisig.into())
+    }
+
+    for (i, signer) in self.signers.iter().enumerate() {
+      if self.signers[..i].contains(signer) {
+        msg!("multisig signer {} listed twice", signer.to_string());
+        return Err(ReeMetaError::InvalidMultisig.into())
+      }
+    }
+
+    Ok(())
+  }
+
+  pub fn size(&self) -> usize {
+    1 // initialized boolean
+    + 1 // threshold
+    + 4 + 32 * self.signers.len()
+  }
+}
+
+/// program wide settings, a single PDA per program
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProgramConfig {
+  pub is_initialized: bool,
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+  pub admin: Pubkey,
+  pub protocol_fee: u16, // basis points of each sale, 0 for none
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+  pub fee_recipient: Pubkey,
+  pub paused: bool,
+}
+
+impl ProgramConfig {
+  pub fn from_account_info(account_info: &AccountInfo) -> Result<ProgramConfig, ProgramError> {
+    let data = &account_info.data.borrow();
+    let config: ProgramConfig = try_from_slice_unchecked(data)?;
+    Ok(config)
+  }
+
+  pub fn find_program_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    pda::find_config_address(program_id)
+  }
+
+  pub fn validate(&self) -> ProgramResult {
+    if self.protocol_fee > MAX_PROTOCOL_FEE_BPS {
+      msg!("protocol fee of {} basis points is over the cap of {}", self.protocol_fee, MAX_PROTOCOL_FEE_BPS);
+      return Err(ReeMetaError::InvalidConfig.into())
+    }
+    Ok(())
+  }
+
+  pub fn size() -> usize {
+    1 // initialized boolean
+    + 32 // pubkey admin
+    + 2 // protocol fee
+    + 32 // pubkey fee recipient
+    + 1 // paused boolean
+  }
+}
+
+/// limited update rights handed to a delegate until expiry
 #[repr(C)]
 #[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DelegateRecord {
+  pub is_initialized: bool,
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+  pub metadata: Pubkey,
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+  pub delegate: Pubkey,
+  pub scope: u8, // bitmask of SCOPE_* values
+  pub expiry: UnixTimestamp, // 8
+}
+
+impl DelegateRecord {
+  pub fn from_account_info(account_info: &AccountInfo) -> Result<DelegateRecord, ProgramError> {
+    let data = &account_info.data.borrow();
+    let record: DelegateRecord = try_from_slice_unchecked(data)?;
+    Ok(record)
+  }
+
+  /// the record has to grant every bit of the scope to the delegate on this metadata until expiry
+  pub fn check(&self, metadata: &Pubkey, delegate: &Pubkey, scope: u8, now: UnixTimestamp) -> ProgramResult {
+    if !self.is_initialized || self.delegate != *delegate || self.metadata != *metadata {
+      return Err(ReeMetaError::InvalidDelegate.into())
+    }
+
+    if self.scope & scope != scope {
+      msg!("delegate scope {} does not include {}", self.scope, scope);
+      return Err(ReeMetaError::DelegateScopeNotGranted.into())
+    }
+
+    if now >= self.expiry {
+      return Err(ReeMetaError::DelegateExpired.into())
+    }
+
+    Ok(())
+  }
+
+  pub fn find_program_address(program_id: &Pubkey, metadata: &Pubkey, delegate: &Pubkey) -> (Pubkey, u8) {
+    pda::find_delegate_record_address(program_id, metadata, delegate)
+  }
+
+  pub fn size() -> usize {
+    1 // initialized boolean
+    + 32 // pubkey metadata
+    + 32 // pubkey delegate
+    + 1 // scope
+    + 8 // expiry
+  }
+}
+
+/// a royalty share converted into a fungible fraction mint. the distributor takes the place
+/// of the holder in the royalties and receives their payouts. lamport payouts can't follow
+/// token transfers so each fraction earns a 1/supply share of every payout. what the unstaked
+/// fractions earn is reserved and handed to them pro rata when they are staked
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FractionDistributor {
+  pub is_initialized: bool,
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+  pub nft_mint: Pubkey,
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+  pub holder: Pubkey, // royalty holder that fractionalized the share
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+  pub fraction_mint: Pubkey,
+  pub supply: u64, // 8 fractions minted, fixed once the mint authority is dropped
+  pub total_staked: u64, // 8
+  pub reward_per_fraction: u128, // 16 lamports per fraction times REWARD_PRECISION
+  pub tracked: u64, // 8 lamports above rent counted in reward_per_fraction and not yet claimed
+  pub reserved: u128, // 16 lamports times REWARD_PRECISION earned by the unstaked fractions
+}
+
+impl FractionDistributor {
+  pub fn from_account_info(account_info: &AccountInfo) -> Result<FractionDistributor, ProgramError> {
+    let data = &account_info.data.borrow();
+    let distributor: FractionDistributor = try_from_slice_unchecked(data)?;
+    Ok(distributor)
+  }
+
+  pub fn find_program_address(program_id: &Pubkey, mint_id: &Pubkey, holder: &Pubkey) -> (Pubkey, u8) {
+    pda::find_distributor_address(program_id, mint_id, holder)
+  }
+
+  fn unstaked(&self) -> Result<u64, ProgramError> {
+    Ok(self.supply.checked_sub(self.total_staked).ok_or(ReeMetaError::MathOverflow)?)
+  }
+
+  /// count the lamports that arrived since the last sync over the whole supply, the
+  /// share of the unstaked fractions goes to reserved
+  pub fn sync(&mut self, available: u64) -> ProgramResult {
+    if self.supply == 0 {
+      return Ok(())
+    }
+
+    let incoming = available.saturating_sub(self.tracked);
+    let increase = (incoming as u128)
+      .checked_mul(REWARD_PRECISION)
+      .ok_or(ReeMetaError::MathOverflow)?
+      / self.supply as u128;
+    self.reward_per_fraction = self.reward_per_fraction
+      .checked_add(increase)
+      .ok_or(ReeMetaError::MathOverflow)?;
+    self.reserved = increase
+      .checked_mul(self.unstaked()? as u128)
+      .and_then(|earned| self.reserved.checked_add(earned))
+      .ok_or(ReeMetaError::MathOverflow)?;
+    self.tracked = self.tracked
+      .checked_add(incoming)
+      .ok_or(ReeMetaError::MathOverflow)?;
+
+    Ok(())
+  }
+
+  /// count amount unstaked fractions as staked and return the lamports of the reserve they
+  /// earned while unstaked. unstaked fractions are fungible so each takes the same share
+  fn stake(&mut self, amount: u64) -> Result<u64, ProgramError> {
+    let unstaked = self.unstaked()?;
+    if amount > unstaked {
+      return Err(ReeMetaError::MathOverflow.into())
+    }
+
+    let lamports = match unstaked {
+      0 => 0,
+      // reserved * amount / unstaked without overflowing, the remainder part fits in u128
+      unstaked => {
+        let unstaked = unstaked as u128;
+        let share = self.reserved / unstaked * amount as u128
+          + self.reserved % unstaked * amount as u128 / unstaked;
+        share / REWARD_PRECISION
+      },
+    };
+    // what is left of the share below a lamport stays in the reserve
+    self.reserved -= lamports * REWARD_PRECISION;
+    self.total_staked += amount;
+
+    Ok(u64::try_from(lamports).map_err(|_| ReeMetaError::MathOverflow)?)
+  }
+
+  fn unstake(&mut self, amount: u64) -> ProgramResult {
+    self.total_staked = self.total_staked
+      .checked_sub(amount)
+      .ok_or(ReeMetaError::MathOverflow)?;
+    Ok(())
+  }
+
+  pub fn size() -> usize {
+    1 // initialized boolean
+    + 32 // pubkey nft mint
+    + 32 // pubkey holder
+    + 32 // pubkey fraction mint
+    + 8 // supply
+    + 8 // total staked
+    + 16 // reward per fraction
+    + 8 // tracked
+    + 16 // reserved
+  }
+}
+
+/// fractions staked by one owner in a distributor
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FractionStake {
+  pub is_initialized: bool,
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+  pub distributor: Pubkey,
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+  pub owner: Pubkey,
+  pub amount: u64, // 8
+  pub reward_debt: u128, // 16 amount times reward_per_fraction at the last settle
+  pub pending: u64, // 8 lamports earned and not claimed
+}
+
+impl FractionStake {
+  pub fn from_account_info(account_info: &AccountInfo) -> Result<FractionStake, ProgramError> {
+    let data = &account_info.data.borrow();
+    let stake: FractionStake = try_from_slice_unchecked(data)?;
+    Ok(stake)
+  }
+
+  pub fn find_program_address(program_id: &Pubkey, distributor: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    pda::find_fraction_stake_address(program_id, distributor, owner)
+  }
+
+  fn earned(amount: u64, distributor: &FractionDistributor) -> Result<u128, ProgramError> {
+    Ok((amount as u128)
+      .checked_mul(distributor.reward_per_fraction)
+      .ok_or(ReeMetaError::MathOverflow)?
+      / REWARD_PRECISION)
+  }
+
+  /// move what the stake earned since the last settle into pending, the distributor
+  /// has to be synced first
+  pub fn settle(&mut self, distributor: &FractionDistributor) -> ProgramResult {
+    let earned = Self::earned(self.amount, distributor)?;
+    let lamports = u64::try_from(earned.saturating_sub(self.reward_debt))
+      .map_err(|_| ReeMetaError::MathOverflow)?;
+    self.pending = self.pending
+      .checked_add(lamports)
+      .ok_or(ReeMetaError::MathOverflow)?;
+    self.reward_debt = earned;
+    Ok(())
+  }
+
+  fn set_amount(&mut self, amount: u64, distributor: &FractionDistributor) -> ProgramResult {
+    self.amount = amount;
+    self.reward_debt = Self::earned(amount, distributor)?;
+    Ok(())
+  }
+
+  /// stake more fractions after a settle, they bring what they earned while unstaked
+  pub fn deposit(&mut self, amount: u64, distributor: &mut FractionDistributor) -> ProgramResult {
+    let total = self.amount
+      .checked_add(amount)
+      .ok_or(ReeMetaError::MathOverflow)?;
+    let reserved = distributor.stake(amount)?;
+    self.pending = self.pending
+      .checked_add(reserved)
+      .ok_or(ReeMetaError::MathOverflow)?;
+    self.set_amount(total, distributor)
+  }
+
+  /// unstake fractions after a settle, they earn into the reserve from now on
+  pub fn withdraw(&mut self, amount: u64, distributor: &mut FractionDistributor) -> ProgramResult {
+    let total = self.amount
+      .checked_sub(amount)
+      .ok_or(ReeMetaError::InsufficientStake)?;
+    distributor.unstake(amount)?;
+    self.set_amount(total, distributor)
+  }
+
+  pub fn size() -> usize {
+    1 // initialized boolean
+    + 32 // pubkey distributor
+    + 32 // pubkey owner
+    + 8 // amount
+    + 16 // reward debt
+    + 8 // pending
+  }
+}
+
+/// the fields every Metadata starts with, readable without knowing the kind
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetadataHeader {
+  pub kind: Kind,
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
+  pub mint: Pubkey,
+  pub is_modifiable: bool,
+  pub update_type: UpdateType,
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey::option"))]
+  pub collection: Option<Pubkey>,
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey::option"))]
+  pub update_authority: Option<Pubkey>,
+}
+
+impl MetadataHeader {
+  pub fn from_account_info(account_info: &AccountInfo) -> Result<MetadataHeader, ProgramError> {
+    let data = &account_info.data.borrow();
+    let header: MetadataHeader = try_from_slice_unchecked(data)?;
+    Ok(header)
+  }
+}
+
+#[repr(C)]
+#[derive(BorshSerialize, BorshDeserialize, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Metadata<
   T: MetadataData + BorshDeserialize + BorshSerialize + PartialEq + Clone
 >{
   // TODO:should add in a version field
   // pub version: u8,
   pub kind: Kind,
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey"))]
   pub mint: Pubkey,
   pub is_modifiable: bool,
   pub update_type: UpdateType,
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey::option"))]
   pub collection: Option<Pubkey>,
+  #[cfg_attr(feature = "serde", serde(with = "crate::serde_pubkey::option"))]
   pub update_authority: Option<Pubkey>,
   pub data: T,
 }
@@ -134,23 +814,36 @@ where
 {
   pub fn from_account_info(account_info: &AccountInfo) -> Result<Metadata<T>, ProgramError> {
     let data = &account_info.data.borrow();
-    let md: Metadata<T> = try_from_slice_unchecked(data)?;
+    Metadata::from_slice(data)
+  }
+
+  /// deserialize account data, metadata written before the data's appended fields reads
+  /// them as their defaults. writing it back needs the account resized to size()
+  pub fn from_slice(data: &[u8]) -> Result<Metadata<T>, ProgramError> {
+    let md: Metadata<T> = try_from_padded_slice(data, T::appended_size())?;
     Ok(md)
   }
 
+  /// the fields every Metadata starts with
+  pub fn header(&self) -> MetadataHeader {
+    MetadataHeader{
+      kind: self.kind,
+      mint: self.mint,
+      is_modifiable: self.is_modifiable,
+      update_type: self.update_type,
+      collection: self.collection,
+      update_authority: self.update_authority,
+    }
+  }
+
   pub fn get_kind(account_info: &AccountInfo) -> Result<Kind, ProgramError> {
     let data = &account_info.data.borrow();
     Ok(Kind::from(&data[0]))
   }
 
+  #[deprecated(note = "use pda::find_metadata_address, the metadata itself is not needed")]
   pub fn find_program_address(&self, program_id: &Pubkey, mint_id: &Pubkey) -> (Pubkey, u8) {
-    let seeds = &[
-      PREFIX.as_bytes(),
-      program_id.as_ref(),
-      mint_id.as_ref()
-    ];
-
-    return Pubkey::find_program_address(seeds, program_id);
+    pda::find_metadata_address(program_id, mint_id)
   }
   
   pub fn size(&self) -> usize {
@@ -179,3 +872,270 @@ where
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use assert_matches::assert_matches;
+
+  fn royalty(share: u16) -> Royalty {
+    Royalty{ address: Pubkey::new_unique(), share, verified: true }
+  }
+
+  fn art_nft(royalties: Vec<Royalty>) -> ArtNft {
+    ArtNft{
+      name: "art".to_string(),
+      symbol: "ART".to_string(),
+      uri: "https://example.com/art.json".to_string(),
+      resale_fee: 10,
+      initial_sale: false,
+      collection: None,
+      royalties,
+      royalty_vault: false,
+      primary_split: Vec::new(),
+      enforced: false,
+      attributes: Vec::new(),
+      soulbound: false,
+    }
+  }
+
+  fn assert_error(result: ProgramResult, error: ReeMetaError) {
+    assert_eq!(result, Err(error.into()));
+  }
+
+  #[test]
+  fn valid_art_nft() {
+    assert_matches!(art_nft(vec![royalty(60), royalty(40)]).validate(), Ok(()));
+
+    let mut at_the_limits = art_nft(vec![royalty(1); MAX_ROYALTIES - 1]);
+    at_the_limits.royalties.iter_mut().for_each(|royalty| royalty.address = Pubkey::new_unique());
+    at_the_limits.royalties.push(royalty(100 - (MAX_ROYALTIES as u16 - 1)));
+    at_the_limits.resale_fee = MAX_RESALE_FEE;
+    at_the_limits.name = "n".repeat(MAX_NAME_LEN);
+    at_the_limits.symbol = "s".repeat(MAX_SYMBOL_LEN);
+    at_the_limits.uri = "u".repeat(MAX_URI_LEN);
+    assert_matches!(at_the_limits.validate(), Ok(()));
+  }
+
+  #[test]
+  fn too_many_royalties() {
+    let royalties: Vec<Royalty> = (0..MAX_ROYALTIES + 1).map(|_| royalty(1)).collect();
+    assert_error(validate_royalties(&royalties), ReeMetaError::TooManyRoyalties);
+  }
+
+  #[test]
+  fn duplicate_royalty_address() {
+    let first = royalty(50);
+    let twice = Royalty{ share: 50, ..first.clone() };
+    assert_error(art_nft(vec![first, twice]).validate(), ReeMetaError::DuplicateRoyaltyAddress);
+  }
+
+  #[test]
+  fn zero_share() {
+    assert_error(art_nft(vec![royalty(100), royalty(0)]).validate(), ReeMetaError::ZeroShare);
+  }
+
+  #[test]
+  fn share_total() {
+    assert_error(art_nft(vec![royalty(60), royalty(30)]).validate(), ReeMetaError::InvalidShareTotal);
+    assert_error(art_nft(vec![royalty(60), royalty(50)]).validate(), ReeMetaError::InvalidShareTotal);
+    assert_error(art_nft(Vec::new()).validate(), ReeMetaError::InvalidShareTotal);
+
+    let mut split = art_nft(vec![royalty(100)]);
+    split.primary_split = vec![royalty(99)];
+    assert_error(split.validate(), ReeMetaError::InvalidShareTotal);
+  }
+
+  #[test]
+  fn strings_too_long() {
+    let mut name = art_nft(vec![royalty(100)]);
+    name.name = "n".repeat(MAX_NAME_LEN + 1);
+    assert_error(name.validate(), ReeMetaError::StringTooLong);
+
+    let mut symbol = art_nft(vec![royalty(100)]);
+    symbol.symbol = "s".repeat(MAX_SYMBOL_LEN + 1);
+    assert_error(symbol.validate(), ReeMetaError::StringTooLong);
+
+    let mut uri = art_nft(vec![royalty(100)]);
+    uri.uri = "u".repeat(MAX_URI_LEN + 1);
+    assert_error(uri.validate(), ReeMetaError::StringTooLong);
+  }
+
+  #[test]
+  fn resale_fee_out_of_range() {
+    let mut art = art_nft(vec![royalty(100)]);
+    art.resale_fee = MAX_RESALE_FEE + 1;
+    assert_error(art.validate(), ReeMetaError::InvalidResaleFee);
+  }
+
+  fn distributor(supply: u64) -> FractionDistributor {
+    FractionDistributor{
+      is_initialized: true,
+      nft_mint: Pubkey::new_unique(),
+      holder: Pubkey::new_unique(),
+      fraction_mint: Pubkey::new_unique(),
+      supply,
+      total_staked: 0,
+      reward_per_fraction: 0,
+      tracked: 0,
+      reserved: 0,
+    }
+  }
+
+  // same steps as the deposit handler for a new stake
+  fn stake(distributor: &mut FractionDistributor, available: u64, amount: u64) -> FractionStake {
+    distributor.sync(available).unwrap();
+    let mut stake = FractionStake{
+      is_initialized: true,
+      distributor: Pubkey::new_unique(),
+      owner: Pubkey::new_unique(),
+      amount: 0,
+      reward_debt: 0,
+      pending: 0,
+    };
+    stake.settle(distributor).unwrap();
+    stake.deposit(amount, distributor).unwrap();
+    stake
+  }
+
+  fn claimable(stake: &mut FractionStake, distributor: &mut FractionDistributor, available: u64) -> u64 {
+    distributor.sync(available).unwrap();
+    stake.settle(distributor).unwrap();
+    stake.pending
+  }
+
+  #[test]
+  fn late_stake_takes_its_share_of_earlier_payouts() {
+    let mut distributor = distributor(10);
+    distributor.sync(1_000).unwrap();
+    distributor.sync(3_000).unwrap();
+    distributor.sync(6_000).unwrap();
+    assert_eq!(distributor.tracked, 6_000);
+
+    let mut first = stake(&mut distributor, 6_000, 1);
+    assert_eq!(claimable(&mut first, &mut distributor, 6_000), 600);
+    assert_eq!(claimable(&mut first, &mut distributor, 7_000), 700);
+
+    // the other nine fractions kept their share of every payout
+    let mut rest = stake(&mut distributor, 7_000, 9);
+    assert_eq!(claimable(&mut rest, &mut distributor, 7_000), 6_300);
+    assert_eq!(distributor.reserved, 0);
+  }
+
+  #[test]
+  fn payouts_split_by_supply() {
+    let mut distributor = distributor(100);
+    let mut first = stake(&mut distributor, 0, 50);
+    distributor.sync(1_000).unwrap();
+    let mut second = stake(&mut distributor, 1_000, 25);
+    distributor.sync(2_000).unwrap();
+
+    assert_eq!(claimable(&mut first, &mut distributor, 2_000), 1_000);
+    assert_eq!(claimable(&mut second, &mut distributor, 2_000), 500);
+    // the 25 fractions still unstaked earned a quarter of both payouts
+    assert_eq!(distributor.reserved, 500 * REWARD_PRECISION);
+  }
+
+  #[test]
+  fn withdrawn_fractions_earn_into_the_reserve() {
+    let mut distributor = distributor(10);
+    let mut first = stake(&mut distributor, 0, 10);
+    distributor.sync(1_000).unwrap();
+    first.settle(&distributor).unwrap();
+    first.withdraw(5, &mut distributor).unwrap();
+    assert_eq!(distributor.total_staked, 5);
+
+    assert_eq!(claimable(&mut first, &mut distributor, 2_000), 1_500);
+    let second = stake(&mut distributor, 2_000, 5);
+    assert_eq!(second.pending, 500);
+
+    assert_matches!(
+      first.withdraw(6, &mut distributor),
+      Err(ProgramError::Custom(code)) if code == ReeMetaError::InsufficientStake as u32
+    );
+  }
+
+  #[test]
+  fn stake_beyond_the_supply() {
+    let mut distributor = distributor(10);
+    let mut first = stake(&mut distributor, 0, 8);
+    assert_matches!(
+      first.deposit(3, &mut distributor),
+      Err(ProgramError::Custom(code)) if code == ReeMetaError::MathOverflow as u32
+    );
+  }
+
+  #[test]
+  fn settle_overflow() {
+    let mut distributor = distributor(1);
+    let mut first = stake(&mut distributor, 0, 1);
+    distributor.reward_per_fraction = (u64::MAX as u128 + 1) * REWARD_PRECISION;
+    assert_matches!(
+      first.settle(&distributor),
+      Err(ProgramError::Custom(code)) if code == ReeMetaError::MathOverflow as u32
+    );
+
+    distributor.reward_per_fraction = REWARD_PRECISION;
+    first.pending = u64::MAX;
+    assert_matches!(
+      first.settle(&distributor),
+      Err(ProgramError::Custom(code)) if code == ReeMetaError::MathOverflow as u32
+    );
+  }
+
+  #[test]
+  fn sync_overflow() {
+    let mut distributor = distributor(1);
+    distributor.tracked = 1;
+    distributor.reward_per_fraction = u128::MAX;
+    assert_matches!(
+      distributor.sync(2),
+      Err(ProgramError::Custom(code)) if code == ReeMetaError::MathOverflow as u32
+    );
+  }
+
+  fn delegate_record(scope: u8, expiry: UnixTimestamp) -> DelegateRecord {
+    DelegateRecord{
+      is_initialized: true,
+      metadata: Pubkey::new_unique(),
+      delegate: Pubkey::new_unique(),
+      scope,
+      expiry,
+    }
+  }
+
+  #[test]
+  fn delegate_within_scope_and_expiry() {
+    let record = delegate_record(SCOPE_URI | SCOPE_ATTRIBUTES, 100);
+    assert_matches!(record.check(&record.metadata, &record.delegate, SCOPE_URI, 99), Ok(()));
+    assert_matches!(record.check(&record.metadata, &record.delegate, SCOPE_URI | SCOPE_ATTRIBUTES, 0), Ok(()));
+  }
+
+  #[test]
+  fn delegate_expired() {
+    let record = delegate_record(SCOPE_ALL, 100);
+    assert_error(record.check(&record.metadata, &record.delegate, SCOPE_URI, 100), ReeMetaError::DelegateExpired);
+    assert_error(record.check(&record.metadata, &record.delegate, SCOPE_URI, 101), ReeMetaError::DelegateExpired);
+  }
+
+  #[test]
+  fn delegate_scope_not_granted() {
+    let record = delegate_record(SCOPE_URI, 100);
+    assert_error(record.check(&record.metadata, &record.delegate, SCOPE_LOCK, 0), ReeMetaError::DelegateScopeNotGranted);
+    // every bit of the scope has to be granted
+    assert_error(
+      record.check(&record.metadata, &record.delegate, SCOPE_URI | SCOPE_ROYALTIES, 0),
+      ReeMetaError::DelegateScopeNotGranted,
+    );
+  }
+
+  #[test]
+  fn delegate_of_another_metadata_or_key() {
+    let record = delegate_record(SCOPE_ALL, 100);
+    assert_error(record.check(&Pubkey::new_unique(), &record.delegate, SCOPE_URI, 0), ReeMetaError::InvalidDelegate);
+    assert_error(record.check(&record.metadata, &Pubkey::new_unique(), SCOPE_URI, 0), ReeMetaError::InvalidDelegate);
+
+    let revoked = DelegateRecord{ is_initialized: false, ..record.clone() };
+    assert_error(revoked.check(&record.metadata, &record.delegate, SCOPE_URI, 0), ReeMetaError::InvalidDelegate);
+  }
+}
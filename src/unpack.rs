@@ -1,4 +1,7 @@
 pub fn unpack_string(data: &[u8]) -> Option<(String, &[u8])> {
+  if data.len() < 4 {
+    return None
+  }
   let (len_chunk, rest) = data.split_at(4);
   let len = match len_chunk.try_into().ok()
     .map(u32::from_le_bytes) {
@@ -1,13 +1,25 @@
 use crate::{
   error::ReeMetaError,
+  pda::{find_metadata_address, find_delegate_record_address, find_config_address},
+  state::{MultisigConfig, DelegateRecord, MetadataHeader, ProgramConfig, UpdateType},
 };
 use solana_program::{
   account_info::{AccountInfo},
+  bpf_loader_upgradeable,
+  clock::Clock,
   entrypoint::ProgramResult,
+  msg,
+  program::{invoke, invoke_signed},
   program_error::ProgramError,
   program_option::COption,
   program_pack::{IsInitialized, Pack},
   pubkey::Pubkey,
+  system_instruction,
+  sysvar::{rent::Rent, Sysvar},
+};
+use spl_token_2022::{
+  extension::StateWithExtensions,
+  state::{Mint, Account as TokenAccount},
 };
 
 
@@ -46,4 +58,383 @@ pub fn assert_owned_by(account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
   } else {
       Ok(())
   }
-}
\ No newline at end of file
+}
+
+/// the metadata account must be owned by the program and be the PDA of its mint
+pub fn assert_metadata_account(
+  program_id: &Pubkey,
+  metadata_info: &AccountInfo,
+) -> Result<MetadataHeader, ProgramError> {
+  assert_owned_by(metadata_info, program_id)?;
+
+  let header = MetadataHeader::from_account_info(metadata_info)?;
+  assert_metadata_address(program_id, metadata_info, &header.mint)?;
+
+  Ok(header)
+}
+
+/// the metadata account must be the PDA derived from mint
+pub fn assert_metadata_address(
+  program_id: &Pubkey,
+  metadata_info: &AccountInfo,
+  mint: &Pubkey,
+) -> Result<u8, ProgramError> {
+  let (metadata_key, metadata_bump_seed) = find_metadata_address(program_id, mint);
+  if metadata_key != *metadata_info.key {
+    msg!("Invalid PDA");
+    return Err(ReeMetaError::InvalidMetadataAccount.into())
+  }
+
+  Ok(metadata_bump_seed)
+}
+
+/// check the update authority of a metadata account. a wallet authority has to sign,
+/// a multisig authority is its config account and needs threshold members signing
+/// anywhere in the instruction accounts
+pub fn assert_update_authority(
+  program_id: &Pubkey,
+  update_type: &UpdateType,
+  update_authority: &Option<Pubkey>,
+  update_authority_info: &AccountInfo,
+  accounts: &[AccountInfo],
+) -> ProgramResult {
+  let authority = match update_authority {
+    None => return Err(ReeMetaError::NoUpdateAuthority.into()),
+    Some(authority) => authority,
+  };
+
+  if *update_authority_info.key != *authority {
+    return Err(ReeMetaError::InvalidUpdateAuthority.into())
+  }
+
+  match update_type {
+    UpdateType::Multisig => {
+      assert_owned_by(update_authority_info, program_id)?;
+      let config = MultisigConfig::from_account_info(update_authority_info)?;
+      if !config.is_initialized {
+        return Err(ReeMetaError::Uninitialized.into())
+      }
+
+      let mut matched: Vec<&Pubkey> = Vec::new();
+      for account in accounts.iter().filter(|account| account.is_signer) {
+        if config.signers.contains(account.key) && !matched.contains(&account.key) {
+          matched.push(account.key);
+        }
+      }
+
+      if matched.len() < config.threshold as usize {
+        msg!("{} of {} required multisig signers", matched.len(), config.threshold);
+        return Err(ReeMetaError::MultisigThresholdNotMet.into())
+      }
+    },
+    _ => {
+      if !update_authority_info.is_signer {
+        return Err(ReeMetaError::InvalidUpdateAuthority.into())
+      }
+    }
+  }
+
+  Ok(())
+}
+
+/// check the update authority like assert_update_authority, or a delegate signing in
+/// its place with an unexpired delegate record granting scope anywhere in the accounts
+pub fn assert_update_authority_or_delegate(
+  program_id: &Pubkey,
+  metadata_key: &Pubkey,
+  update_type: &UpdateType,
+  update_authority: &Option<Pubkey>,
+  authority_info: &AccountInfo,
+  accounts: &[AccountInfo],
+  scope: u8,
+) -> ProgramResult {
+  if *update_authority == None || *update_authority == Some(*authority_info.key) {
+    return assert_update_authority(program_id, update_type, update_authority, authority_info, accounts)
+  }
+
+  let (record_key, _) = find_delegate_record_address(program_id, metadata_key, authority_info.key);
+  let record_info = match accounts.iter().find(|account| *account.key == record_key) {
+    None => return Err(ReeMetaError::InvalidUpdateAuthority.into()),
+    Some(record_info) => record_info,
+  };
+  assert_owned_by(record_info, program_id)?;
+
+  if !authority_info.is_signer {
+    return Err(ReeMetaError::InvalidDelegate.into())
+  }
+
+  let record = DelegateRecord::from_account_info(record_info)?;
+  record.check(metadata_key, authority_info.key, scope, Clock::get()?.unix_timestamp)
+}
+
+/// the authority has to sign and be the upgrade authority in the ProgramData account of the program
+pub fn assert_upgrade_authority(
+  program_id: &Pubkey,
+  program_data_info: &AccountInfo,
+  authority_info: &AccountInfo,
+) -> ProgramResult {
+  let (program_data_key, _) = Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::ID);
+  if program_data_key != *program_data_info.key {
+    msg!("invalid program data account");
+    return Err(ReeMetaError::InvalidAccountKey.into())
+  }
+  assert_owned_by(program_data_info, &bpf_loader_upgradeable::ID)?;
+
+  // UpgradeableLoaderState::ProgramData: u32 variant 3, u64 slot, then the optional authority
+  let data = program_data_info.data.borrow();
+  let upgrade_authority = match data.get(..45) {
+    Some([3, 0, 0, 0, _, _, _, _, _, _, _, _, 1, authority @ ..]) => Pubkey::try_from(authority).ok(),
+    _ => None,
+  };
+
+  if upgrade_authority != Some(*authority_info.key) || !authority_info.is_signer {
+    msg!("only the upgrade authority of the program can do this");
+    return Err(ReeMetaError::InvalidConfig.into())
+  }
+  Ok(())
+}
+
+/// find the program config anywhere in the accounts. it has to be passed so it can't be
+/// skipped to get around a pause, an uninitialized config reads as the default. requiring
+/// it is the breaking change of 2.0.0
+pub fn load_program_config(
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> Result<ProgramConfig, ProgramError> {
+  let (config_key, _) = find_config_address(program_id);
+  let config_info = match accounts.iter().find(|account| *account.key == config_key) {
+    None => {
+      msg!("program config account missing");
+      return Err(ReeMetaError::InvalidConfig.into())
+    },
+    Some(config_info) => config_info,
+  };
+
+  if config_info.data_is_empty() {
+    return Ok(ProgramConfig::default())
+  }
+
+  assert_owned_by(config_info, program_id)?;
+  ProgramConfig::from_account_info(config_info)
+}
+
+/// accept either spl_token or Token-2022 as the token program
+pub fn assert_token_program(token_program_info: &AccountInfo) -> ProgramResult {
+  if *token_program_info.key != spl_token::ID && *token_program_info.key != spl_token_2022::ID {
+      msg!("invalid token program");
+      return Err(ReeMetaError::InvalidInstruction.into())
+  }
+  Ok(())
+}
+
+/// unpack the base mint state of a mint owned by the given token program,
+/// Token-2022 extensions are skipped
+pub fn unpack_mint(mint_info: &AccountInfo, token_program_id: &Pubkey) -> Result<Mint, ProgramError> {
+  assert_owned_by(mint_info, token_program_id)?;
+  let data = mint_info.data.borrow();
+  let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+  Ok(mint.base)
+}
+
+/// unpack the base token account state of an account owned by the given token program,
+/// Token-2022 extensions are skipped
+pub fn unpack_token_account(
+  token_account_info: &AccountInfo,
+  token_program_id: &Pubkey,
+) -> Result<TokenAccount, ProgramError> {
+  assert_owned_by(token_account_info, token_program_id)?;
+  let data = token_account_info.data.borrow();
+  let account = StateWithExtensions::<TokenAccount>::unpack(&data)?;
+  Ok(account.base)
+}
+
+/// a sale moves the NFT into a token account of the payer, who can't be the seller
+pub fn assert_buyer(
+  payer_account_info: &AccountInfo,
+  target_account_info: &AccountInfo,
+  buyer_token_account_info: &AccountInfo,
+  token_program_id: &Pubkey,
+) -> ProgramResult {
+  if payer_account_info.key == target_account_info.key {
+    msg!("the buyer can't be the seller");
+    return Err(ReeMetaError::InvalidBuyer.into())
+  }
+
+  let buyer_token_account = unpack_token_account(buyer_token_account_info, token_program_id)?;
+  if buyer_token_account.owner != *payer_account_info.key {
+    msg!("buyer token account must belong to the payer");
+    return Err(ReeMetaError::InvalidTokenAccount.into())
+  }
+
+  Ok(())
+}
+
+/// check the accounts of moving the NFT of mint_id: the owner signs and holds it in the
+/// source, the destination holds the same mint
+pub fn assert_nft_transfer(
+  mint_id: &Pubkey,
+  mint_info: &AccountInfo,
+  owner_info: &AccountInfo,
+  source_info: &AccountInfo,
+  destination_info: &AccountInfo,
+  token_program_info: &AccountInfo,
+) -> Result<(Mint, TokenAccount, TokenAccount), ProgramError> {
+  assert_token_program(token_program_info)?;
+
+  if *mint_info.key != *mint_id {
+    msg!("mint does not match the metadata");
+    return Err(ReeMetaError::InvalidTokenAccount.into())
+  }
+
+  if !owner_info.is_signer {
+    msg!("token owner must sign");
+    return Err(ReeMetaError::InvalidInstruction.into())
+  }
+
+  let mint: Mint = unpack_mint(mint_info, token_program_info.key)?;
+  let source: TokenAccount = unpack_token_account(source_info, token_program_info.key)?;
+  let destination: TokenAccount = unpack_token_account(destination_info, token_program_info.key)?;
+
+  if source.mint != *mint_id
+    || destination.mint != *mint_id
+    || source.owner != *owner_info.key
+    || source.amount != 1
+  {
+    return Err(ReeMetaError::InvalidTokenAccount.into())
+  }
+
+  Ok((mint, source, destination))
+}
+
+/// move the NFT from the source to the destination, signed by the owner
+pub fn transfer_nft<'a>(
+  mint: &Mint,
+  mint_info: &AccountInfo<'a>,
+  owner_info: &AccountInfo<'a>,
+  source_info: &AccountInfo<'a>,
+  destination_info: &AccountInfo<'a>,
+  token_program_info: &AccountInfo<'a>,
+) -> ProgramResult {
+  invoke(
+    &spl_token_2022::instruction::transfer_checked(
+      token_program_info.key,
+      source_info.key,
+      mint_info.key,
+      destination_info.key,
+      owner_info.key,
+      &[],
+      1,
+      mint.decimals
+    )?,
+    &[
+      source_info.clone(),
+      mint_info.clone(),
+      destination_info.clone(),
+      owner_info.clone(),
+    ]
+  )
+}
+
+/// fund, allocate and assign a PDA owned by the program
+pub fn create_pda_account<'a>(
+  payer_account_info: &AccountInfo<'a>,
+  new_account_info: &AccountInfo<'a>,
+  system_info: &AccountInfo<'a>,
+  program_id: &Pubkey,
+  rent: &Rent,
+  size: usize,
+  signer_seeds: &[&[u8]],
+) -> ProgramResult {
+  let required_lamports = rent
+    .minimum_balance(size)
+    .max(1)
+    .saturating_sub(new_account_info.lamports());
+
+  if required_lamports > 0 {
+    invoke(
+      &system_instruction::transfer(
+        payer_account_info.key,
+        new_account_info.key,
+        required_lamports
+      ),
+      &[
+        payer_account_info.clone(),
+        new_account_info.clone(),
+        system_info.clone(),
+      ]
+    )?;
+  }
+
+  let accounts = &[
+    new_account_info.clone(),
+    system_info.clone(),
+  ];
+
+  invoke_signed(
+    &system_instruction::allocate(new_account_info.key, size.try_into().unwrap()),
+    accounts,
+    &[signer_seeds]
+  )?;
+  invoke_signed(
+    &system_instruction::assign(new_account_info.key, program_id),
+    accounts,
+    &[signer_seeds]
+  )?;
+
+  Ok(())
+}
+
+/// top up rent from the payer and realloc a program owned account to new_size
+pub fn resize_account<'a>(
+  account_info: &AccountInfo<'a>,
+  payer_account_info: &AccountInfo<'a>,
+  system_info: &AccountInfo<'a>,
+  rent: &Rent,
+  new_size: usize,
+) -> ProgramResult {
+  let required_lamports = rent
+    .minimum_balance(new_size)
+    .max(1)
+    .saturating_sub(account_info.lamports());
+
+  if required_lamports > 0 {
+    invoke(
+      &system_instruction::transfer(
+        payer_account_info.key,
+        account_info.key,
+        required_lamports
+      ),
+      &[
+        payer_account_info.clone(),
+        account_info.clone(),
+        system_info.clone(),
+      ]
+    )?;
+  }
+
+  if account_info.data_len() != new_size {
+    account_info.realloc(new_size, false)?;
+  }
+
+  Ok(())
+}
+
+
+/// quote and escape a string for JSON output
+pub fn json_string(value: &str) -> String {
+  let mut out = String::with_capacity(value.len() + 2);
+  out.push('"');
+  for c in value.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      '\r' => out.push_str("\\r"),
+      '\t' => out.push_str("\\t"),
+      c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+  out
+}